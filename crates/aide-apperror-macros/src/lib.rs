@@ -0,0 +1,563 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+  parse_macro_input, spanned::Spanned, Data, DeriveInput, Expr, Field, Ident, LitInt, LitStr, Path,
+};
+
+/// Generates `status_code`, `description`, `headers`, `create_example` and `details_schema` on the `<Enum>Code`
+/// discriminants enum, and `to_app_error_output` on the error enum itself. A `codes` module
+/// holds a marker type per variant, to build `ApiErrors` sets with.
+///
+/// Every variant needs an `#[api_error(..)]` attribute:
+/// - `status = 401`: the StatusCode of the response (required).
+/// - `description = "..."`: the description used in the docs (required).
+/// - `message = "..."`: the `error` of the output, defaults to the `Display` of the variant.
+/// - `details`: fill in `error_details` from the `ErrorDetails` impl of the single field of the
+///   variant, its `Details` type is documented as the `error_details` of this code.
+/// - `example = path::to::fn`: `fn() -> AppErrorOutput` overriding the generated example.
+/// - `header(name = "WWW-Authenticate", value = "ApiKey", description = "...")`: a header sent
+///   with the response, may be repeated. `with = path::to::fn` in place of `value` builds it from
+///   the response instead, with a `fn(&AppErrorOutput) -> Option<String>`, e.g. a `Retry-After`.
+#[proc_macro_derive(ApiError, attributes(api_error))]
+pub fn derive_api_error(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implements `RequestSources` for a struct whose fields each name the part of the request they
+/// are extracted from, for `RequestValidate`:
+/// - `#[from(path)]`: the path parameter of the same name.
+/// - `#[from(query)]`: the query parameter of the same name.
+/// - `#[from(header)]`: the header named after the field, with `-` for `_`, or
+///   `#[from(header = "If-Match")]`.
+/// - `#[from(body)]`: the field of the same name of the JSON body.
+///
+/// The `doc`, `serde`, `schemars` and `validate` attributes of a field are kept in the docs of its
+/// source.
+#[proc_macro_derive(RequestSources, attributes(from, serde, schemars))]
+pub fn derive_request_sources(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  expand_request_sources(input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+  Path,
+  Query,
+  Header,
+  Body,
+}
+
+impl Source {
+  const ALL: [Self; 4] = [Self::Path, Self::Query, Self::Header, Self::Body];
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::Path => "Path",
+      Self::Query => "Query",
+      Self::Header => "Headers",
+      Self::Body => "Body",
+    }
+  }
+
+  fn extractor(self, ty: &Ident) -> TokenStream2 {
+    match self {
+      Self::Path => quote!(::axum::extract::Path<#ty>),
+      Self::Query => quote!(::axum::extract::Query<#ty>),
+      Self::Header => quote!(::aide_apperror::extractors::HeaderFields<#ty>),
+      Self::Body => quote!(::axum::Json<#ty>),
+    }
+  }
+}
+
+fn expand_request_sources(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let ident = &input.ident;
+  if !input.generics.params.is_empty() {
+    return Err(syn::Error::new(
+      input.generics.span(),
+      "`RequestSources` can not be derived for generic structs",
+    ));
+  }
+  let Data::Struct(data) = &input.data else {
+    return Err(syn::Error::new(
+      ident.span(),
+      "`RequestSources` can only be derived for structs",
+    ));
+  };
+  let syn::Fields::Named(fields) = &data.fields else {
+    return Err(syn::Error::new(
+      ident.span(),
+      "`RequestSources` can only be derived for structs with named fields",
+    ));
+  };
+
+  let mut sourced = Vec::new();
+  let mut header_fields = Vec::new();
+  for field in &fields.named {
+    let mut source = None;
+    let mut header_name: Option<LitStr> = None;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("from")) {
+      attr.parse_nested_meta(|meta| {
+        let found = if meta.path.is_ident("path") {
+          Source::Path
+        } else if meta.path.is_ident("query") {
+          Source::Query
+        } else if meta.path.is_ident("body") {
+          Source::Body
+        } else if meta.path.is_ident("header") {
+          if meta.input.peek(syn::Token![=]) {
+            header_name = Some(meta.value()?.parse()?);
+          }
+          Source::Header
+        } else {
+          return Err(meta.error("expected `path`, `query`, `header` or `body`"));
+        };
+        if source.replace(found).is_some() {
+          return Err(meta.error("a field can only have one source"));
+        }
+        Ok(())
+      })?;
+    }
+    let source = source.ok_or_else(|| {
+      syn::Error::new(
+        field.span(),
+        "missing `#[from(path|query|header|body)]` attribute",
+      )
+    })?;
+    let name = field.ident.as_ref().expect("named field");
+    let ty = &field.ty;
+    let kept = field.attrs.iter().filter(|a| {
+      ["doc", "serde", "schemars", "validate"]
+        .iter()
+        .any(|kept| a.path().is_ident(kept))
+    });
+    let rename = (source == Source::Header).then(|| {
+      let header = header_name
+        .map(|lit| lit.value())
+        .unwrap_or_else(|| name.to_string().replace('_', "-"));
+      header_fields.push((name.to_string(), header.clone()));
+      quote!(#[serde(rename = #header)])
+    });
+    sourced.push((
+      source,
+      name,
+      quote! {
+        #(#kept)*
+        #rename
+        #name: #ty
+      },
+    ));
+  }
+
+  let mut structs = Vec::new();
+  let mut hiddens = Vec::new();
+  let mut extract = Vec::new();
+  let mut assign = Vec::new();
+  let mut extractors = Vec::new();
+  for source in Source::ALL {
+    let fields: Vec<_> = sourced.iter().filter(|(s, ..)| *s == source).collect();
+    if fields.is_empty() {
+      continue;
+    }
+    let schema_name = format!("{ident}{}", source.name());
+    let hidden = format_ident!("__{}", schema_name);
+    let value = format_ident!("__{}", source.name().to_lowercase());
+    let defs = fields.iter().map(|(.., def)| def);
+    structs.push(quote! {
+      #[derive(::serde::Deserialize, ::schemars::JsonSchema)]
+      #[serde(rename = #schema_name)]
+      struct #hidden {
+        #(#defs,)*
+      }
+    });
+
+    let extractor = source.extractor(&hidden);
+    hiddens.push(hidden);
+    extract.push(match source {
+      Source::Body => quote! {
+        let req = ::axum::extract::Request::from_parts(parts, body);
+        let #value = <#extractor as ::axum::extract::FromRequest<__S>>::from_request(req, state)
+          .await?
+          .0;
+      },
+      _ => quote! {
+        let #value =
+          <#extractor as ::axum::extract::FromRequestParts<__S>>::from_request_parts(&mut parts, state)
+            .await?
+            .0;
+      },
+    });
+    assign.extend(
+      fields
+        .iter()
+        .map(|(_, name, _)| quote!(#name: #value.#name)),
+    );
+    extractors.push(extractor);
+  }
+
+  let has_body = sourced.iter().any(|(s, ..)| *s == Source::Body);
+  let has_parts = sourced.iter().any(|(s, ..)| *s != Source::Body);
+  let parts = if has_parts {
+    quote!(mut parts)
+  } else {
+    quote!(parts)
+  };
+  let body = if has_body { quote!(body) } else { quote!(_) };
+  let (fields, headers): (Vec<_>, Vec<_>) = header_fields.into_iter().unzip();
+
+  Ok(quote! {
+    const _: () = {
+      #(#structs)*
+
+      #[::axum::async_trait]
+      impl ::aide_apperror::extractors::RequestSources for #ident {
+        async fn from_sources<__S: ::core::marker::Send + ::core::marker::Sync>(
+          req: ::axum::extract::Request,
+          state: &__S,
+        ) -> ::core::result::Result<Self, ::aide_apperror::AppError> {
+          let (#parts, #body) = req.into_parts();
+          #(#extract)*
+          ::core::result::Result::Ok(Self { #(#assign,)* })
+        }
+
+        fn rejection_codes() -> ::std::vec::Vec<::aide_apperror::AppErrorCode> {
+          [#(<#extractors as ::aide_apperror::extractors::RejectionCodes>::REJECTION_CODES),*].concat()
+        }
+
+        fn serde_field_names(
+          mut errors: ::validator::ValidationErrors,
+        ) -> ::validator::ValidationErrors {
+          #(
+            if let ::core::option::Option::Some(kind) = errors.errors_mut().remove(#fields) {
+              errors.errors_mut().insert(#headers, kind);
+            }
+          )*
+          #(let errors = ::aide_apperror::details::serde_field_names::<#hiddens>(errors);)*
+          errors
+        }
+
+        fn document_sources(
+          ctx: &mut ::aide::gen::GenContext,
+          operation: &mut ::aide::openapi::Operation,
+        ) {
+          #(<#extractors as ::aide::OperationInput>::operation_input(ctx, operation);)*
+        }
+      }
+    };
+  })
+}
+
+struct VariantAttrs {
+  ident: Ident,
+  status: LitInt,
+  description: Expr,
+  message: Option<Expr>,
+  details: Option<Field>,
+  example: Option<Path>,
+  headers: Vec<HeaderAttrs>,
+}
+
+struct HeaderAttrs {
+  name: Expr,
+  description: Expr,
+  value: HeaderValueAttr,
+}
+
+enum HeaderValueAttr {
+  Fixed(Expr),
+  With(Path),
+}
+
+impl VariantAttrs {
+  fn parse(variant: &syn::Variant) -> syn::Result<Self> {
+    let mut status = None;
+    let mut description = None;
+    let mut message = None;
+    let mut details = false;
+    let mut example = None;
+    let mut headers = Vec::new();
+
+    for attr in variant
+      .attrs
+      .iter()
+      .filter(|a| a.path().is_ident("api_error"))
+    {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("status") {
+          let lit: LitInt = meta.value()?.parse()?;
+          match lit.base10_parse::<u16>()? {
+            100..=999 => status = Some(lit),
+            _ => {
+              return Err(syn::Error::new(
+                lit.span(),
+                "status must be within 100..=999",
+              ))
+            }
+          }
+        } else if meta.path.is_ident("description") {
+          description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("message") {
+          message = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("details") {
+          details = true;
+        } else if meta.path.is_ident("example") {
+          example = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("header") {
+          let mut name = None;
+          let mut description = None;
+          let mut value = None;
+          meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+              name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("description") {
+              description = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("value") {
+              value = Some(HeaderValueAttr::Fixed(meta.value()?.parse()?));
+            } else if meta.path.is_ident("with") {
+              value = Some(HeaderValueAttr::With(meta.value()?.parse()?));
+            } else {
+              return Err(meta.error("unsupported header attribute"));
+            }
+            Ok(())
+          })?;
+          match (name, description, value) {
+            (Some(name), Some(description), Some(value)) => headers.push(HeaderAttrs {
+              name,
+              description,
+              value,
+            }),
+            _ => {
+              return Err(
+                meta.error("`header` requires `name`, `description` and either `value` or `with`"),
+              )
+            }
+          }
+        } else {
+          return Err(meta.error("unsupported api_error attribute"));
+        }
+        Ok(())
+      })?;
+    }
+
+    let details = match (details, variant.fields.len()) {
+      (false, _) => None,
+      (true, 1) => variant.fields.iter().next().cloned(),
+      (true, _) => {
+        return Err(syn::Error::new(
+          variant.span(),
+          "`details` requires a variant with exactly one field",
+        ))
+      }
+    };
+
+    let missing = |name: &str| {
+      syn::Error::new(
+        variant.span(),
+        format!(
+          "missing `#[api_error({name} = ..)]` on variant `{}`",
+          variant.ident
+        ),
+      )
+    };
+
+    Ok(Self {
+      ident: variant.ident.clone(),
+      status: status.ok_or_else(|| missing("status"))?,
+      description: description.ok_or_else(|| missing("description"))?,
+      message,
+      details,
+      example,
+      headers,
+    })
+  }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+  let Data::Enum(data) = &input.data else {
+    return Err(syn::Error::new(
+      input.span(),
+      "ApiError can only be derived for enums",
+    ));
+  };
+
+  let error = &input.ident;
+  let code = format_ident!("{}Code", error);
+  let variants = data
+    .variants
+    .iter()
+    .map(VariantAttrs::parse)
+    .collect::<syn::Result<Vec<_>>>()?;
+
+  let status_arms = variants.iter().map(|v| {
+    let VariantAttrs { ident, status, .. } = v;
+    quote! {
+      #code::#ident => ::axum::http::StatusCode::from_u16(#status)
+        .expect("status is validated by #[derive(ApiError)]"),
+    }
+  });
+
+  let description_arms = variants.iter().map(|v| {
+    let VariantAttrs {
+      ident, description, ..
+    } = v;
+    quote! { #code::#ident => #description, }
+  });
+
+  let header_arms = variants.iter().map(|v| {
+    let ident = &v.ident;
+    let headers = v.headers.iter().map(|header| {
+      let HeaderAttrs {
+        name, description, ..
+      } = header;
+      let value = match &header.value {
+        HeaderValueAttr::Fixed(value) => {
+          quote! { ::aide_apperror::ErrorHeaderValue::Fixed(#value) }
+        }
+        HeaderValueAttr::With(with) => quote! { ::aide_apperror::ErrorHeaderValue::With(#with) },
+      };
+      quote! {
+        ::aide_apperror::ErrorHeader {
+          name: #name,
+          description: #description,
+          value: #value,
+        }
+      }
+    });
+    quote! {
+      #code::#ident => {
+        const HEADERS: &[::aide_apperror::ErrorHeader] = &[#(#headers),*];
+        HEADERS
+      }
+    }
+  });
+
+  let example_arms = variants.iter().map(|v| {
+    let ident = &v.ident;
+    if let Some(example) = &v.example {
+      return quote! { #code::#ident => #example(), };
+    }
+    let message = match &v.message {
+      Some(message) => quote! { #message },
+      None => {
+        let message = LitStr::new(&ident.to_string(), ident.span());
+        quote! { #message }
+      }
+    };
+    quote! {
+      #code::#ident => ::aide_apperror::AppErrorOutput::new(#code::#ident, #message, None),
+    }
+  });
+
+  let output_arms = variants.iter().map(|v| {
+    let ident = &v.ident;
+    let message = match &v.message {
+      Some(message) => quote! { #message },
+      None => quote! { &self.to_string() },
+    };
+    let (pattern, details) = match &v.details {
+      Some(field) => {
+        let ty = &field.ty;
+        let pattern = match &field.ident {
+          Some(field) => quote! { #error::#ident { #field: inner } },
+          None => quote! { #error::#ident(inner) },
+        };
+        let details = quote! {
+          Some(unsafe {
+            ::serde_json::to_value(<#ty as ::aide_apperror::ErrorDetails>::details(inner))
+              .unwrap_unchecked()
+          })
+        };
+        (pattern, details)
+      }
+      None => (quote! { #error::#ident { .. } }, quote! { None }),
+    };
+    quote! {
+      #pattern => ::aide_apperror::AppErrorOutput::new(#code::#ident, #message, #details),
+    }
+  });
+
+  let details_schema_arms = variants.iter().map(|v| {
+    let ident = &v.ident;
+    match &v.details {
+      Some(Field { ty, .. }) => quote! {
+        #code::#ident => Some(
+          gen.subschema_for::<<#ty as ::aide_apperror::ErrorDetails>::Details>()
+        ),
+      },
+      None => quote! { #code::#ident => None, },
+    }
+  });
+
+  let markers = variants.iter().map(|v| {
+    let ident = &v.ident;
+    let doc = format!("Marker of `{code}::{ident}`, to list it in an `ApiErrors` set.");
+    quote! {
+      #[doc = #doc]
+      #[derive(Debug, Clone, Copy)]
+      pub struct #ident;
+
+      impl ::aide_apperror::ErrorCodeMarker for #ident {
+        const CODE: super::#code = super::#code::#ident;
+      }
+    }
+  });
+
+  Ok(quote! {
+    /// A marker type per error code, see `ApiErrors`.
+    pub mod codes {
+      #(#markers)*
+    }
+
+    impl #error {
+      fn to_app_error_output(&self) -> ::aide_apperror::AppErrorOutput {
+        match self {
+          #(#output_arms)*
+        }
+      }
+    }
+
+    impl #code {
+      pub fn status_code(&self) -> ::axum::http::StatusCode {
+        match self {
+          #(#status_arms)*
+        }
+      }
+
+      pub fn description(&self) -> &'static str {
+        match self {
+          #(#description_arms)*
+        }
+      }
+
+      /// The headers sent with responses of this code.
+      pub fn headers(&self) -> &'static [::aide_apperror::ErrorHeader] {
+        match self {
+          #(#header_arms)*
+        }
+      }
+
+      /// An example output for this code, with a nil `error_id` so generated docs are stable.
+      pub fn create_example(&self) -> ::aide_apperror::AppErrorOutput {
+        let example = match self {
+          #(#example_arms)*
+        };
+        example.with_nil_id()
+      }
+
+      /// The schema of `error_details` for this code, `None` if it never has any.
+      pub fn details_schema(
+        &self,
+        gen: &mut ::schemars::gen::SchemaGenerator,
+      ) -> Option<::schemars::schema::Schema> {
+        match self {
+          #(#details_schema_arms)*
+        }
+      }
+    }
+  })
+}
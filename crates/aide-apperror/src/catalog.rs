@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use schemars::{gen::SchemaSettings, schema::Schema};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::{
+  error::{AppErrorCode, AppErrorOutput},
+  policy::ErrorPolicy,
+};
+
+/// Everything documented about an `AppErrorCode`, one entry of `error_catalog`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorCatalogEntry {
+  /// A stable, machine-readable error code.
+  pub code: AppErrorCode,
+  /// The HTTP status code of responses with this code.
+  pub status: u16,
+  /// What this code means.
+  pub description: String,
+  /// The headers sent with responses of this code, e.g. `WWW-Authenticate`, with the values of
+  /// the example.
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub headers: BTreeMap<&'static str, String>,
+  /// An example response body.
+  pub example: AppErrorOutput,
+  /// The schema of `error_details`, absent if this code has none.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub details_schema: Option<Schema>,
+}
+
+/// Every error the server can respond with, in `AppErrorCode` order.
+///
+/// Codes the `policy` remaps are left out, they are never sent.
+pub fn error_catalog(policy: &ErrorPolicy) -> Vec<ErrorCatalogEntry> {
+  // inlined, so each `details_schema` stands on its own
+  let mut gen = SchemaSettings::openapi3()
+    .with(|s| s.inline_subschemas = true)
+    .into_generator();
+  AppErrorCode::iter()
+    .filter(|code| policy.resolve(*code) == *code)
+    .map(|code| {
+      let example = code.create_example();
+      ErrorCatalogEntry {
+        code,
+        status: code.status_code_as_u16(),
+        description: code.description().to_owned(),
+        headers: code
+          .headers()
+          .iter()
+          .filter_map(|header| Some((header.name, header.value_for(&example)?.into_owned())))
+          .collect(),
+        example,
+        details_schema: code.details_schema(&mut gen),
+      }
+    })
+    .collect()
+}
+
+/// `error_catalog` as a Markdown reference page.
+pub fn error_reference_markdown(policy: &ErrorPolicy) -> String {
+  let catalog = error_catalog(policy);
+  let mut md =
+    String::from("# Error reference\n\n| Code | Status | Description |\n|---|---|---|\n");
+  for entry in &catalog {
+    let code = entry.code.as_str();
+    let _ = writeln!(
+      md,
+      "| [`{code}`](#{}) | {} | {} |",
+      code.to_lowercase(),
+      entry.status,
+      entry.description
+    );
+  }
+  for entry in &catalog {
+    let _ = write!(
+      md,
+      "\n## {}\n\nStatus `{}`. {}\n\n```json\n{}\n```\n",
+      entry.code.as_str(),
+      entry.status,
+      entry.description,
+      pretty(&entry.example)
+    );
+    if !entry.headers.is_empty() {
+      md.push_str("\nHeaders:\n\n");
+      for (name, value) in &entry.headers {
+        let _ = writeln!(md, "- `{name}: {value}`");
+      }
+    }
+    if let Some(schema) = &entry.details_schema {
+      let _ = write!(
+        md,
+        "\nThe schema of `error_details`:\n\n```json\n{}\n```\n",
+        pretty(schema)
+      );
+    }
+  }
+  md
+}
+
+/// `error_catalog` as a standalone HTML reference page.
+pub fn error_reference_html(policy: &ErrorPolicy) -> String {
+  let catalog = error_catalog(policy);
+  let mut html = String::from(concat!(
+    "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+    "<title>Error reference</title>\n</head>\n<body>\n<h1>Error reference</h1>\n",
+    "<table>\n<tr><th>Code</th><th>Status</th><th>Description</th></tr>\n",
+  ));
+  for entry in &catalog {
+    let code = entry.code.as_str();
+    let _ = writeln!(
+      html,
+      "<tr><td><a href=\"#{code}\"><code>{code}</code></a></td><td>{}</td><td>{}</td></tr>",
+      entry.status,
+      escape_html(&entry.description)
+    );
+  }
+  html.push_str("</table>\n");
+  for entry in &catalog {
+    let code = entry.code.as_str();
+    let _ = writeln!(
+      html,
+      "<h2 id=\"{code}\">{code}</h2>\n<p>Status <code>{}</code>. {}</p>\n<pre><code>{}</code></pre>",
+      entry.status,
+      escape_html(&entry.description),
+      escape_html(&pretty(&entry.example))
+    );
+    if !entry.headers.is_empty() {
+      html.push_str("<p>Headers:</p>\n<ul>\n");
+      for (name, value) in &entry.headers {
+        let _ = writeln!(html, "<li><code>{name}: {}</code></li>", escape_html(value));
+      }
+      html.push_str("</ul>\n");
+    }
+    if let Some(schema) = &entry.details_schema {
+      let _ = writeln!(
+        html,
+        "<p>The schema of <code>error_details</code>:</p>\n<pre><code>{}</code></pre>",
+        escape_html(&pretty(schema))
+      );
+    }
+  }
+  html.push_str("</body>\n</html>\n");
+  html
+}
+
+fn pretty(value: &impl Serialize) -> String {
+  unsafe { serde_json::to_string_pretty(value).unwrap_unchecked() }
+}
+
+fn escape_html(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
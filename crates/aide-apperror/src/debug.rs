@@ -0,0 +1,154 @@
+use std::{
+  any::type_name,
+  backtrace::Backtrace,
+  error::Error,
+  fmt,
+  sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc, Mutex,
+  },
+};
+
+use axum::{extract::Request, http::Extensions, middleware::Next, response::Response};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{error::AppErrorOutput, problem::rerender};
+
+/// Set to enable debug mode, unless `set_debug_mode` was called.
+pub const DEBUG_MODE_ENV: &str = "APP_ERROR_DEBUG";
+
+const UNSET: u8 = 0;
+const DISABLED: u8 = 1;
+const ENABLED: u8 = 2;
+
+static DEBUG_MODE: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Enable or disable debug mode, overriding `APP_ERROR_DEBUG`.
+///
+/// In debug mode the `error_details` of responses become
+/// `{ "details": <error_details>, "debug": { "source_chain", "backtrace", "extractor" } }`.
+/// Never enable it in production, it reveals internals to clients.
+pub fn set_debug_mode(enabled: bool) {
+  let state = if enabled { ENABLED } else { DISABLED };
+  DEBUG_MODE.store(state, Ordering::Relaxed);
+}
+
+/// Whether debug mode is enabled, by `set_debug_mode` or else by `APP_ERROR_DEBUG=1|true`.
+pub fn debug_mode() -> bool {
+  match DEBUG_MODE.load(Ordering::Relaxed) {
+    UNSET => {
+      let enabled = std::env::var(DEBUG_MODE_ENV).is_ok_and(|v| v == "1" || v == "true");
+      set_debug_mode(enabled);
+      enabled
+    }
+    state => state == ENABLED,
+  }
+}
+
+/// What debug mode adds to `error_details`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorDebug {
+  /// The messages of the errors that caused it, outermost first.
+  pub source_chain: Vec<String>,
+  /// Where an `AppError::Internal` was created.
+  pub backtrace: Option<String>,
+  /// The extractor that rejected the request, see `debug_errors`.
+  pub extractor: Option<&'static str>,
+}
+
+impl ErrorDebug {
+  /// Wraps the `error_details` of `output` along with `self`.
+  pub(crate) fn attach(self, output: &mut AppErrorOutput) {
+    let debug = unsafe { serde_json::to_value(self).unwrap_unchecked() };
+    output.error_details = Some(json!({
+      "details": output.error_details.take(),
+      "debug": debug,
+    }));
+  }
+}
+
+/// The source of an `AppError::Internal` created in debug mode, to keep where it was created.
+///
+/// Transparent otherwise, it displays as its source and has the same source chain.
+#[derive(Debug)]
+pub(crate) struct Traced {
+  source: Box<dyn Error + Send + Sync>,
+  pub(crate) backtrace: Backtrace,
+}
+
+impl Traced {
+  pub(crate) fn new(source: Box<dyn Error + Send + Sync>) -> Self {
+    Self {
+      source,
+      backtrace: Backtrace::force_capture(),
+    }
+  }
+}
+
+impl fmt::Display for Traced {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.source.fmt(f)
+  }
+}
+
+impl Error for Traced {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    self.source.source()
+  }
+}
+
+/// The extractor that rejected a request, filled in by the extractors of this crate.
+///
+/// Only present in debug mode, `debug_errors` adds it to the request.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorOrigin(Arc<Mutex<Option<&'static str>>>);
+
+impl ErrorOrigin {
+  pub fn of(extensions: &Extensions) -> Option<Self> {
+    extensions.get::<Self>().cloned()
+  }
+
+  /// Note `X` as the origin if `result` is a rejection.
+  pub fn track<X, T, E>(origin: Option<Self>, result: Result<T, E>) -> Result<T, E> {
+    if let (Some(origin), Err(_)) = (origin, &result) {
+      *origin.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(type_name::<X>());
+    }
+    result
+  }
+
+  fn get(&self) -> Option<&'static str> {
+    *self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+/// Middleware that adds the rejecting extractor to the debug `error_details`, in debug mode.
+///
+/// ```ignore
+/// router.layer(axum::middleware::from_fn(debug_errors))
+/// ```
+pub async fn debug_errors(mut request: Request, next: Next) -> Response {
+  if !debug_mode() {
+    return next.run(request).await;
+  }
+  let origin = ErrorOrigin::default();
+  request.extensions_mut().insert(origin.clone());
+  let response = next.run(request).await;
+
+  let Some(extractor) = origin.get() else {
+    return response;
+  };
+  let (parts, body) = response.into_parts();
+  let Some(mut output) = parts.extensions.get::<AppErrorOutput>().cloned() else {
+    return Response::from_parts(parts, body);
+  };
+  let Some(debug) = output
+    .error_details
+    .as_mut()
+    .and_then(|d| d.get_mut("debug"))
+  else {
+    return Response::from_parts(parts, body);
+  };
+  debug["extractor"] = Value::from(extractor);
+  rerender(parts, body, output)
+}
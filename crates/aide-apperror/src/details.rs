@@ -0,0 +1,350 @@
+use std::{
+  borrow::Cow,
+  collections::{BTreeMap, HashMap},
+  sync::{Mutex, OnceLock},
+};
+
+use axum::extract::{
+  multipart::{MultipartError, MultipartRejection},
+  rejection::{
+    BytesRejection, FailedToDeserializeFormBody, FormRejection, InvalidFormContentType,
+    JsonDataError, JsonRejection, JsonSyntaxError, MissingJsonContentType, PathRejection,
+    QueryRejection,
+  },
+};
+use schemars::{
+  gen::SchemaGenerator,
+  schema::{RootSchema, Schema, SchemaObject, SingleOrVec},
+  JsonSchema, Map,
+};
+use serde::Serialize;
+use serde_json::Value;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+/// The typed `error_details` of an `AppError` variant, derived from the error it wraps.
+///
+/// Used by `#[api_error(details)]`, both to fill in `error_details` and to document its schema.
+pub trait ErrorDetails {
+  type Details: Serialize + JsonSchema;
+
+  fn details(&self) -> Self::Details;
+}
+
+/// A single validation rule that a field did not pass.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FieldError {
+  /// A JSON pointer (RFC 6901) to the field in the request, e.g. `/items/2/description`.
+  pub pointer: String,
+  /// The validation rule that failed, e.g. `length`.
+  pub code: String,
+  /// Optional message attached to the rule.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub message: Option<String>,
+  /// The parameters of the rule, e.g. `min` and `max`.
+  pub params: BTreeMap<String, Value>,
+}
+
+impl ErrorDetails for ValidationErrors {
+  type Details = Vec<FieldError>;
+
+  fn details(&self) -> Self::Details {
+    let mut fields = Vec::new();
+    collect_field_errors(self, "", &mut fields);
+    fields
+  }
+}
+
+fn collect_field_errors(errors: &ValidationErrors, pointer: &str, out: &mut Vec<FieldError>) {
+  // sorted, so the output does not depend on `HashMap` ordering
+  let errors: BTreeMap<_, _> = errors.errors().iter().collect();
+  for (field, kind) in errors {
+    let pointer = match *field {
+      // struct level `#[validate(schema(..))]` errors belong to the struct itself
+      "__all__" => pointer.to_owned(),
+      field => format!("{pointer}/{}", escape_pointer_token(field)),
+    };
+    match kind {
+      ValidationErrorsKind::Field(errors) => out.extend(errors.iter().map(|e| {
+        FieldError {
+          pointer: pointer.clone(),
+          code: e.code.to_string(),
+          message: e.message.as_ref().map(|m| m.to_string()),
+          params: e
+            .params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+        }
+      })),
+      ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &pointer, out),
+      ValidationErrorsKind::List(list) => list
+        .iter()
+        .for_each(|(i, errors)| collect_field_errors(errors, &format!("{pointer}/{i}"), out)),
+    }
+  }
+}
+
+fn escape_pointer_token(token: &str) -> String {
+  token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Rename the fields of `errors` to the names `T` is deserialized from, so their pointers match
+/// the request.
+///
+/// `validator` follows `#[serde(rename)]` but not `#[serde(rename_all)]`, the names are looked up
+/// in the schema of `T` instead, through nested structs and lists. The schema is only generated
+/// once per type.
+pub fn serde_field_names<T: ?Sized + JsonSchema>(errors: ValidationErrors) -> ValidationErrors {
+  let root = schema_of::<T>();
+  rename_fields(errors, Some(&root.schema), &root.definitions)
+}
+
+/// The schema of `T`, by its `schema_id` which identifies the schemas of types.
+fn schema_of<T: ?Sized + JsonSchema>() -> &'static RootSchema {
+  static SCHEMAS: OnceLock<Mutex<HashMap<Cow<'static, str>, &'static RootSchema>>> =
+    OnceLock::new();
+
+  let mut schemas = SCHEMAS
+    .get_or_init(Default::default)
+    .lock()
+    .unwrap_or_else(|e| e.into_inner());
+  schemas.entry(T::schema_id()).or_insert_with(|| {
+    // one per validated type, kept for the lifetime of the program
+    Box::leak(Box::new(
+      SchemaGenerator::default().into_root_schema_for::<T>(),
+    ))
+  })
+}
+
+fn rename_fields(
+  errors: ValidationErrors,
+  schema: Option<&'static SchemaObject>,
+  definitions: &'static Map<String, Schema>,
+) -> ValidationErrors {
+  let properties = schema.and_then(|schema| properties(schema, definitions));
+  let mut renamed = ValidationErrors::new();
+  for (field, kind) in errors.into_errors() {
+    let property = properties.and_then(|properties| property(properties, field));
+    let schema = property.and_then(|(_, schema)| resolve(schema, definitions));
+    let kind = match kind {
+      ValidationErrorsKind::Struct(errors) => {
+        ValidationErrorsKind::Struct(Box::new(rename_fields(*errors, schema, definitions)))
+      }
+      ValidationErrorsKind::List(list) => {
+        let items = schema.and_then(|schema| items(schema, definitions));
+        ValidationErrorsKind::List(
+          list
+            .into_iter()
+            .map(|(i, errors)| (i, Box::new(rename_fields(*errors, items, definitions))))
+            .collect(),
+        )
+      }
+      kind => kind,
+    };
+    let field = property.map_or(field, |(name, _)| name.as_str());
+    renamed.errors_mut().insert(field, kind);
+  }
+  renamed
+}
+
+/// The property of `field`, named as is or by one of the `#[serde(rename_all)]` rules.
+fn property<'a>(
+  properties: &'a Map<String, Schema>,
+  field: &str,
+) -> Option<(&'a String, &'a Schema)> {
+  properties.get_key_value(field).or_else(|| {
+    RENAME_RULES
+      .iter()
+      .find_map(|rule| properties.get_key_value(&rule(field)))
+  })
+}
+
+/// The `#[serde(rename_all)]` rules that change a snake_case field name, as serde applies them.
+const RENAME_RULES: [fn(&str) -> String; 5] = [
+  |field| field.to_ascii_uppercase(),
+  pascal_case,
+  |field| {
+    let pascal = pascal_case(field);
+    let mut chars = pascal.chars();
+    chars.next().map_or_else(String::new, |first| {
+      first.to_ascii_lowercase().to_string() + chars.as_str()
+    })
+  },
+  |field| field.replace('_', "-"),
+  |field| field.to_ascii_uppercase().replace('_', "-"),
+];
+
+fn pascal_case(field: &str) -> String {
+  let mut pascal = String::with_capacity(field.len());
+  let mut capitalize = true;
+  for ch in field.chars() {
+    if ch == '_' {
+      capitalize = true;
+    } else if capitalize {
+      pascal.push(ch.to_ascii_uppercase());
+      capitalize = false;
+    } else {
+      pascal.push(ch);
+    }
+  }
+  pascal
+}
+
+fn resolve<'a>(
+  schema: &'a Schema,
+  definitions: &'a Map<String, Schema>,
+) -> Option<&'a SchemaObject> {
+  let Schema::Object(schema) = schema else {
+    return None;
+  };
+  match &schema.reference {
+    Some(reference) => {
+      let name = reference.strip_prefix("#/definitions/")?;
+      resolve(definitions.get(name)?, definitions)
+    }
+    None => Some(schema),
+  }
+}
+
+/// The schemas `schema` is made of, e.g. the `anyOf` of an `Option`.
+fn subschemas<'a>(
+  schema: &'a SchemaObject,
+  definitions: &'a Map<String, Schema>,
+) -> impl Iterator<Item = &'a SchemaObject> {
+  let subschemas = schema.subschemas.iter().flat_map(|subschemas| {
+    [&subschemas.all_of, &subschemas.any_of, &subschemas.one_of]
+      .into_iter()
+      .flatten()
+      .flatten()
+  });
+  subschemas.filter_map(|schema| resolve(schema, definitions))
+}
+
+fn properties<'a>(
+  schema: &'a SchemaObject,
+  definitions: &'a Map<String, Schema>,
+) -> Option<&'a Map<String, Schema>> {
+  match &schema.object {
+    Some(object) if !object.properties.is_empty() => Some(&object.properties),
+    _ => subschemas(schema, definitions).find_map(|schema| properties(schema, definitions)),
+  }
+}
+
+fn items<'a>(
+  schema: &'a SchemaObject,
+  definitions: &'a Map<String, Schema>,
+) -> Option<&'a SchemaObject> {
+  match schema.array.as_ref().and_then(|array| array.items.as_ref()) {
+    Some(SingleOrVec::Single(items)) => resolve(items, definitions),
+    _ => subschemas(schema, definitions).find_map(|schema| items(schema, definitions)),
+  }
+}
+
+/// Rejections are detailed by their message.
+macro_rules! rejection_details {
+  ($($rejection:ty),* $(,)?) => {
+    $(
+      impl ErrorDetails for $rejection {
+        type Details = String;
+
+        fn details(&self) -> Self::Details {
+          self.to_string()
+        }
+      }
+    )*
+  };
+}
+
+rejection_details!(
+  PathRejection,
+  QueryRejection,
+  JsonRejection,
+  JsonSyntaxError,
+  JsonDataError,
+  MissingJsonContentType,
+  FormRejection,
+  FailedToDeserializeFormBody,
+  InvalidFormContentType,
+  BytesRejection,
+  MultipartRejection,
+  MultipartError,
+  serde::de::value::Error,
+  Cow<'static, str>,
+);
+
+#[cfg(test)]
+mod tests {
+  use serde::Deserialize;
+  use validator::Validate;
+
+  use super::*;
+
+  #[derive(Deserialize, JsonSchema, Validate)]
+  #[serde(rename_all = "camelCase")]
+  struct NewOrder {
+    #[validate(length(min = 5))]
+    due_date: String,
+    #[serde(rename = "customer")]
+    #[validate(length(min = 5))]
+    customer_name: String,
+    #[validate]
+    line_items: Vec<LineItem>,
+    #[validate]
+    main_item: Option<LineItem>,
+  }
+
+  #[derive(Deserialize, JsonSchema, Validate)]
+  #[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+  struct LineItem {
+    #[validate(length(min = 5))]
+    item_description: String,
+  }
+
+  #[derive(Deserialize, JsonSchema, Validate)]
+  struct Lookalikes {
+    #[validate(length(min = 5))]
+    a_b: String,
+    #[serde(rename = "ab")]
+    #[validate(length(min = 5))]
+    other: String,
+  }
+
+  fn pointers(errors: ValidationErrors) -> Vec<String> {
+    let mut pointers: Vec<_> = errors.details().into_iter().map(|e| e.pointer).collect();
+    pointers.sort();
+    pointers
+  }
+
+  #[test]
+  fn names_fields_after_serde() {
+    let item = |description: &str| LineItem {
+      item_description: description.into(),
+    };
+    let order = NewOrder {
+      due_date: "soon".into(),
+      customer_name: "me".into(),
+      line_items: vec![item("a long enough one"), item("shrt")],
+      main_item: Some(item("tiny")),
+    };
+    let errors = serde_field_names::<NewOrder>(order.validate().unwrap_err());
+    assert_eq!(
+      pointers(errors),
+      [
+        "/customer",
+        "/dueDate",
+        "/lineItems/1/ITEM-DESCRIPTION",
+        "/mainItem/ITEM-DESCRIPTION",
+      ]
+    );
+  }
+
+  #[test]
+  fn only_renames_as_serde_does() {
+    let lookalikes = Lookalikes {
+      a_b: "a".into(),
+      other: "b".into(),
+    };
+    let errors = serde_field_names::<Lookalikes>(lookalikes.validate().unwrap_err());
+    assert_eq!(pointers(errors), ["/a_b", "/ab"]);
+  }
+}
@@ -0,0 +1,117 @@
+use std::marker::PhantomData;
+
+use axum::response::IntoResponse;
+
+use crate::{
+  error::{AppError, AppErrorCode, AppErrorOutput},
+  extractors::set_inferred_responses,
+};
+
+/// Implemented by the marker types in `error::codes`.
+pub trait ErrorCodeMarker {
+  const CODE: AppErrorCode;
+}
+
+/// A set of `AppErrorCode`s, either a single marker or a tuple of markers.
+pub trait ErrorSet {
+  fn codes() -> Vec<AppErrorCode>;
+
+  fn contains(code: AppErrorCode) -> bool {
+    Self::codes().contains(&code)
+  }
+}
+
+impl<C: ErrorCodeMarker> ErrorSet for C {
+  fn codes() -> Vec<AppErrorCode> {
+    vec![C::CODE]
+  }
+}
+
+macro_rules! impl_error_set {
+  ($($marker:ident),+) => {
+    impl<$($marker: ErrorCodeMarker),+> ErrorSet for ($($marker,)+) {
+      fn codes() -> Vec<AppErrorCode> {
+        vec![$($marker::CODE),+]
+      }
+    }
+  };
+}
+
+impl_error_set!(A);
+impl_error_set!(A, B);
+impl_error_set!(A, B, C);
+impl_error_set!(A, B, C, D);
+impl_error_set!(A, B, C, D, E);
+impl_error_set!(A, B, C, D, E, F);
+impl_error_set!(A, B, C, D, E, F, G);
+impl_error_set!(A, B, C, D, E, F, G, H);
+impl_error_set!(A, B, C, D, E, F, G, H, I);
+impl_error_set!(A, B, C, D, E, F, G, H, I, J);
+impl_error_set!(A, B, C, D, E, F, G, H, I, J, K);
+impl_error_set!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// An `AppError` that only documents the codes of `S`, e.g.
+/// `Result<T, ApiErrors<(codes::NotFound, codes::AuthKeyMissing)>>`.
+///
+/// Anything that converts into an `AppError` converts into `ApiErrors`, so `?` keeps working.
+/// A code outside of `S` is never sent, it becomes an `AppError::Internal`, which is always
+/// documented. Debug builds panic on it, so the missing code can't go unnoticed.
+pub struct ApiErrors<S>(AppError, PhantomData<S>);
+
+impl<S> ApiErrors<S> {
+  pub fn into_inner(self) -> AppError {
+    self.0
+  }
+}
+
+impl<S, E: Into<AppError>> From<E> for ApiErrors<S> {
+  fn from(error: E) -> Self {
+    Self(error.into(), PhantomData)
+  }
+}
+
+impl<S> std::fmt::Debug for ApiErrors<S> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+impl<S: ErrorSet> IntoResponse for ApiErrors<S> {
+  fn into_response(self) -> axum::response::Response {
+    let code = AppErrorCode::from(&self.0);
+    debug_assert!(
+      S::contains(code),
+      "returned the error code {}, which is not documented",
+      code.as_str()
+    );
+    if !S::contains(code) {
+      return AppError::internal(UndocumentedCode(self.0)).into_response();
+    }
+    self.0.into_response()
+  }
+}
+
+/// An error returned by an `ApiErrors` that does not list its code.
+#[derive(Debug, thiserror::Error)]
+#[error("the error code {} is not documented by the handler", AppErrorCode::from(.0).as_str())]
+struct UndocumentedCode(#[source] AppError);
+
+impl<S: ErrorSet> aide::OperationOutput for ApiErrors<S> {
+  type Inner = AppErrorOutput;
+
+  fn operation_response(
+    ctx: &mut aide::gen::GenContext,
+    operation: &mut aide::openapi::Operation,
+  ) -> Option<aide::openapi::Response> {
+    <AppError as aide::OperationOutput>::operation_response(ctx, operation)
+  }
+
+  fn inferred_responses(
+    ctx: &mut aide::gen::GenContext,
+    operation: &mut aide::openapi::Operation,
+  ) -> Vec<(Option<u16>, aide::openapi::Response)> {
+    let codes = S::codes().into_iter().chain([AppErrorCode::Internal]);
+    set_inferred_responses(ctx, operation, codes);
+    Vec::new()
+  }
+}
@@ -5,7 +5,7 @@ use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
-use crate::error::AppError;
+use crate::{debug::ErrorOrigin, error::AppError, extractors::validated::validate};
 
 /// Deserialize request body into json, and apply validation checks
 #[derive(aide::OperationIo)]
@@ -21,10 +21,8 @@ where
   type Rejection = Infallible;
 
   async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
-    let value = axum::Json::from_request(req, state)
-      .await
-      .map_err(|e| AppError::from(e))
-      .and_then(|v: axum::Json<T>| v.0.validate().map(|_| v.0).map_err(|e| AppError::from(e)));
-    Ok(Self(value))
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = validate(axum::Json::from_request(req, state).await).map(|v: axum::Json<T>| v.0);
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
   }
 }
@@ -0,0 +1,19 @@
+//! Extractors that never reject, the outcome is handed to the handler as a `Result<T, AppError>`
+//! instead. Responses are not inferred, it is up to the route docs to describe them, except for the
+//! `Validation` response of `Validated` and `ValidatedWithState`.
+
+pub mod json;
+pub mod multipart;
+pub mod path;
+pub mod query;
+pub mod request;
+pub mod validated;
+pub mod with_state;
+
+pub use json::*;
+pub use multipart::*;
+pub use path::*;
+pub use query::*;
+pub use request::*;
+pub use validated::*;
+pub use with_state::*;
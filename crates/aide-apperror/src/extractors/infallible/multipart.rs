@@ -0,0 +1,38 @@
+use std::convert::Infallible;
+
+use axum::extract::{FromRequest, Request};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  error::AppError,
+  multipart::{extract_form, set_form_body, MultipartForm},
+};
+
+/// Deserialize a `multipart/form-data` body, enforce the `FILE_RULES` of its files, and apply
+/// validation checks
+pub struct MultipartValidate<T>(pub Result<T, AppError>);
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for MultipartValidate<T>
+where
+  T: DeserializeOwned + Validate + MultipartForm + JsonSchema,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = extract_form(req, state).await;
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
+  }
+}
+
+/// Only documents the `multipart/form-data` body.
+impl<T: MultipartForm + JsonSchema> aide::OperationInput for MultipartValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_form_body::<T>(ctx, operation);
+  }
+}
@@ -5,7 +5,7 @@ use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
-use crate::error::AppError;
+use crate::{debug::ErrorOrigin, error::AppError, extractors::validated::validate};
 
 /// Extract Path variables, and apply validation checks
 #[derive(aide::OperationIo)]
@@ -22,10 +22,8 @@ where
 
   async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
     use axum::extract::Path;
-    let value = Path::from_request_parts(parts, state)
-      .await
-      .map_err(|e| AppError::from(e))
-      .and_then(|v: Path<T>| v.0.validate().map(|_| v.0).map_err(|e| AppError::from(e)));
-    Ok(Self(value))
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(Path::from_request_parts(parts, state).await).map(|v: Path<T>| v.0);
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
   }
 }
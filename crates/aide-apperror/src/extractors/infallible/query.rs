@@ -0,0 +1,34 @@
+use std::convert::Infallible;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{debug::ErrorOrigin, error::AppError, extractors::validated::validate};
+
+/// Extract the query string, and apply validation checks
+pub struct QueryValidate<T>(pub Result<T, AppError>);
+
+#[axum::async_trait]
+impl<T, S> FromRequestParts<S> for QueryValidate<T>
+where
+  T: DeserializeOwned + Validate + JsonSchema + Send,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    use axum::extract::Query;
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(Query::from_request_parts(parts, state).await).map(|v: Query<T>| v.0);
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
+  }
+}
+
+/// Only documents the parameters of `T`, along with their validation rules.
+impl<T: JsonSchema> aide::OperationInput for QueryValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    <axum::extract::Query<T> as aide::OperationInput>::operation_input(ctx, operation)
+  }
+}
@@ -0,0 +1,36 @@
+use std::convert::Infallible;
+
+use axum::extract::{FromRequest, Request};
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  error::AppError,
+  extractors::request::{extract_request, RequestSources},
+};
+
+/// Extract `T` from the path, query, headers and body of the request, and apply validation checks
+/// to the assembled struct
+pub struct RequestValidate<T>(pub Result<T, AppError>);
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for RequestValidate<T>
+where
+  T: RequestSources + Validate,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = extract_request(req, state).await;
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
+  }
+}
+
+/// Only documents the sources of `T`.
+impl<T: RequestSources> aide::OperationInput for RequestValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    T::document_sources(ctx, operation)
+  }
+}
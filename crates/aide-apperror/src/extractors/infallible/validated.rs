@@ -0,0 +1,61 @@
+use std::{convert::Infallible, ops::Deref};
+
+use axum::{
+  extract::{FromRequest, FromRequestParts, Request},
+  http::request::Parts,
+};
+use schemars::JsonSchema;
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+  extractors::{set_inferred_response, validated::validate},
+};
+
+/// Run extractor `E`, and apply validation checks to what it extracted,
+/// e.g. `Validated<Json<T>>` or `Validated<Query<T>>`
+pub struct Validated<E>(pub Result<E, AppError>);
+
+#[axum::async_trait]
+impl<E, S> FromRequestParts<S> for Validated<E>
+where
+  E: FromRequestParts<S> + Deref,
+  E::Target: Validate + JsonSchema,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(E::from_request_parts(parts, state).await);
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
+  }
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequest<S> for Validated<E>
+where
+  E: FromRequest<S> + Deref,
+  E::Target: Validate + JsonSchema,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = validate(E::from_request(req, state).await);
+    Ok(Self(ErrorOrigin::track::<Self, _, _>(origin, value)))
+  }
+}
+
+/// Documents what `E` documents, e.g. the body of `Json<T>`, and the `Validation` response. The
+/// rejections of `E` are left to the route docs.
+impl<E: aide::OperationInput> aide::OperationInput for Validated<E> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_inferred_response(ctx, operation, AppErrorCode::Validation);
+    E::operation_input(ctx, operation)
+  }
+}
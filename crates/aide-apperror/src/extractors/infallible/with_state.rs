@@ -0,0 +1,70 @@
+use std::{convert::Infallible, ops::Deref};
+
+use axum::{
+  extract::{FromRequest, FromRequestParts, Request},
+  http::request::Parts,
+};
+
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+  extractors::{set_inferred_response, with_state::validate_with_state},
+  validate::ValidateWithState,
+};
+
+/// Run extractor `E`, then the `ValidateWithState` checks of what it extracted,
+/// e.g. `ValidatedWithState<JsonValidate<T>>` with the `JsonValidate` that rejects
+pub struct ValidatedWithState<E>(pub Result<E, AppError>);
+
+#[axum::async_trait]
+impl<E, S> FromRequestParts<S> for ValidatedWithState<E>
+where
+  E: FromRequestParts<S> + Deref + Send,
+  E::Target: ValidateWithState<S>,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = E::from_request_parts(parts, state)
+      .await
+      .map_err(AppError::from);
+    let value = match value {
+      Ok(value) => validate_with_state::<Self, _, _>(origin, value, state).await,
+      Err(e) => Err(e),
+    };
+    Ok(Self(value))
+  }
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequest<S> for ValidatedWithState<E>
+where
+  E: FromRequest<S> + Deref + Send,
+  E::Target: ValidateWithState<S>,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = E::from_request(req, state).await.map_err(AppError::from);
+    let value = match value {
+      Ok(value) => validate_with_state::<Self, _, _>(origin, value, state).await,
+      Err(e) => Err(e),
+    };
+    Ok(Self(value))
+  }
+}
+
+/// Documents what `E` documents, e.g. the body of `JsonValidate<T>`, and the `Validation`
+/// response. The rejections of `E` are left to the route docs.
+impl<E: aide::OperationInput> aide::OperationInput for ValidatedWithState<E> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_inferred_response(ctx, operation, AppErrorCode::Validation);
+    E::operation_input(ctx, operation)
+  }
+}
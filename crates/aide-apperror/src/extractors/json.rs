@@ -1,14 +1,27 @@
+use std::ops::Deref;
+
 use axum::extract::{FromRequest, Request};
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
-use crate::error::{AppError, AppErrorCode};
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+};
 
-use super::set_inferred_response;
+use super::{set_inferred_response, validated::validate};
 
 pub struct JsonValidate<T>(pub T);
 
+impl<T> Deref for JsonValidate<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
 #[axum::async_trait]
 impl<T, S> FromRequest<S> for JsonValidate<T>
 where
@@ -18,17 +31,23 @@ where
   type Rejection = AppError;
 
   async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
-    let value: T = axum::Json::from_request(req, state).await?.0;
-    value.validate()?;
-    Ok(Self(value))
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = validate(axum::Json::from_request(req, state).await).map(|v: axum::Json<T>| v.0);
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
   }
 }
 
 impl<T: JsonSchema> aide::OperationInput for JsonValidate<T> {
   fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
-    [AppErrorCode::Validation, AppErrorCode::JsonRejection]
-      .into_iter()
-      .for_each(|status| set_inferred_response(ctx, operation, status));
+    [
+      AppErrorCode::Validation,
+      AppErrorCode::JsonDataError,
+      AppErrorCode::JsonSyntaxError,
+      AppErrorCode::MissingJsonContentType,
+      AppErrorCode::JsonPayloadTooLarge,
+    ]
+    .into_iter()
+    .for_each(|status| set_inferred_response(ctx, operation, status));
 
     // <axum::Json<AppErrorOutput> as aide::OperationInput>::operation_input(ctx, operation);
     <axum::Json<T> as aide::OperationInput>::operation_input(ctx, operation)
@@ -0,0 +1,389 @@
+use crate::{
+  error::{AppErrorCode, AppErrorOutput, ErrorHeaderValue},
+  locale::DEFAULT_LANGUAGE,
+  problem::{ProblemDetails, PROBLEM_JSON},
+};
+use aide::transform::TransformOpenApi;
+use schemars::JsonSchema;
+use strum::IntoEnumIterator;
+
+pub mod infallible;
+pub mod json;
+pub mod multipart;
+pub mod path;
+pub mod query;
+pub mod request;
+pub mod validated;
+pub mod with_state;
+
+pub use json::*;
+pub use multipart::*;
+pub use path::*;
+pub use query::*;
+pub use request::*;
+pub use validated::*;
+pub use with_state::*;
+
+/// Document `status` as one of the responses of `operation`, using its description and example.
+///
+/// Codes sharing a status, e.g. `PathRejection` and `JsonSyntaxError`, are merged into a single
+/// response, with a named example per code and their descriptions combined.
+pub fn set_inferred_response(
+  ctx: &mut aide::gen::GenContext,
+  operation: &mut aide::openapi::Operation,
+  status: AppErrorCode,
+) {
+  use aide::openapi::{ReferenceOr, StatusCode};
+  use indexmap::map::Entry;
+
+  let res = app_error_operation_response(ctx, operation, status);
+  let status = status.status_code_as_u16();
+
+  let responses = operation.responses.get_or_insert_with(Default::default);
+
+  match responses.responses.entry(StatusCode::Code(status)) {
+    Entry::Occupied(mut entry) => match entry.get_mut() {
+      ReferenceOr::Item(existing) => merge_response(existing, res),
+      ReferenceOr::Reference { .. } => ctx.error(aide::Error::InferredResponseConflict(status)),
+    },
+    Entry::Vacant(entry) => {
+      entry.insert(ReferenceOr::Item(res));
+    }
+  }
+}
+
+/// `set_inferred_response` for each of `codes`.
+pub fn set_inferred_responses(
+  ctx: &mut aide::gen::GenContext,
+  operation: &mut aide::openapi::Operation,
+  codes: impl IntoIterator<Item = AppErrorCode>,
+) {
+  codes
+    .into_iter()
+    .for_each(|status| set_inferred_response(ctx, operation, status));
+}
+
+/// Undo `set_inferred_response` of `status`, returns whether `operation` documented it.
+///
+/// Other codes sharing its status stay documented, the response is removed along with the last.
+pub(crate) fn unset_inferred_response(
+  operation: &mut aide::openapi::Operation,
+  status: AppErrorCode,
+) -> bool {
+  use aide::openapi::{ReferenceOr, StatusCode};
+
+  let Some(responses) = &mut operation.responses else {
+    return false;
+  };
+  let code = StatusCode::Code(status.status_code_as_u16());
+  let Some(ReferenceOr::Item(res)) = responses.responses.get_mut(&code) else {
+    return false;
+  };
+
+  let mut documented = false;
+  let mut remaining = Vec::new();
+  for media_type in res.content.values_mut() {
+    documented |= media_type.examples.shift_remove(status.as_str()).is_some();
+    remaining.extend(media_type.examples.keys().cloned());
+  }
+  if !documented {
+    return false;
+  }
+  if remaining.is_empty() {
+    responses.responses.shift_remove(&code);
+    return true;
+  }
+
+  // keep what `status` shares with the remaining codes
+  let remaining: Vec<_> = AppErrorCode::iter()
+    .filter(|c| remaining.iter().any(|name| name == c.as_str()))
+    .collect();
+  if !remaining
+    .iter()
+    .any(|c| c.description() == status.description())
+  {
+    res.description = res
+      .description
+      .split("\n\n")
+      .filter(|d| *d != status.description())
+      .collect::<Vec<_>>()
+      .join("\n\n");
+  }
+  for header in status.headers() {
+    if !remaining
+      .iter()
+      .any(|c| c.headers().iter().any(|h| h.name == header.name))
+    {
+      res.headers.shift_remove(header.name);
+    }
+  }
+  true
+}
+
+/// Runs `f` on every operation of `api`, to document what applies to all of them.
+pub(crate) fn for_each_operation(
+  mut api: TransformOpenApi,
+  mut f: impl FnMut(&mut aide::gen::GenContext, &mut aide::openapi::Operation),
+) -> TransformOpenApi {
+  let Some(paths) = &mut api.inner_mut().paths else {
+    return api;
+  };
+  aide::gen::in_context(|ctx| {
+    for (_, path) in &mut paths.paths {
+      let aide::openapi::ReferenceOr::Item(path) = path else {
+        continue;
+      };
+      for (_, operation) in aide::util::iter_operations_mut(path) {
+        f(ctx, operation);
+      }
+    }
+  });
+  api
+}
+
+fn merge_response(existing: &mut aide::openapi::Response, res: aide::openapi::Response) {
+  if existing.description.is_empty() {
+    existing.description = res.description;
+  } else if !existing
+    .description
+    .split("\n\n")
+    .any(|d| d == res.description)
+  {
+    existing.description = format!("{}\n\n{}", existing.description, res.description);
+  }
+
+  for (name, header) in res.headers {
+    existing.headers.entry(name).or_insert(header);
+  }
+
+  for (media, media_type) in res.content {
+    let Some(existing) = existing.content.get_mut(&media) else {
+      existing.content.insert(media, media_type);
+      continue;
+    };
+    if existing.schema.is_none() {
+      existing.schema = media_type.schema;
+    }
+    // a single `example` can not be combined with `examples`
+    if let Some(example) = existing.example.take() {
+      existing.examples.insert(
+        "example".to_owned(),
+        aide::openapi::ReferenceOr::Item(aide::openapi::Example {
+          value: Some(example),
+          ..Default::default()
+        }),
+      );
+    }
+    for (name, example) in media_type.examples {
+      existing.examples.entry(name).or_insert(example);
+    }
+  }
+}
+
+fn app_error_operation_response(
+  ctx: &mut aide::gen::GenContext,
+  _operation: &mut aide::openapi::Operation,
+  status: AppErrorCode,
+) -> aide::openapi::Response {
+  let example = status.create_example();
+  let problem_example = ProblemDetails::from(example.clone());
+
+  aide::openapi::Response {
+    // description: schema.metadata().description.clone().unwrap_or_default(),
+    description: status.description().to_owned(),
+    content: indexmap::IndexMap::from_iter([
+      (
+        "application/json".into(),
+        app_error_media_type::<AppErrorOutput>(ctx, status, example),
+      ),
+      (
+        PROBLEM_JSON.into(),
+        app_error_media_type::<ProblemDetails>(ctx, status, problem_example),
+      ),
+    ]),
+    headers: app_error_headers(ctx, status),
+    ..Default::default()
+  }
+}
+
+/// `Content-Language`, `Request-Id`, and the headers of `status` itself, e.g. `WWW-Authenticate`.
+fn app_error_headers(
+  ctx: &mut aide::gen::GenContext,
+  status: AppErrorCode,
+) -> indexmap::IndexMap<String, aide::openapi::ReferenceOr<aide::openapi::Header>> {
+  let content_language = header(
+    "The language of the error message, see `Accept-Language`.".to_owned(),
+    ctx.schema.subschema_for::<String>(),
+    Some(DEFAULT_LANGUAGE.into()),
+  );
+  let request_id = header(
+    "The `error_id` of the response, to report the error with.".to_owned(),
+    ctx.schema.subschema_for::<uuid::Uuid>(),
+    Some(uuid::Uuid::nil().to_string()),
+  );
+  let example = status.create_example();
+  let headers = status.headers().iter().map(|error_header| {
+    let value = error_header.value_for(&example).map(|v| v.into_owned());
+    let mut header = match error_header.value {
+      ErrorHeaderValue::Fixed(fixed) => {
+        let schema = schemars::schema::SchemaObject {
+          instance_type: Some(schemars::schema::InstanceType::String.into()),
+          enum_values: Some(vec![fixed.into()]),
+          ..Default::default()
+        };
+        header(error_header.description.to_owned(), schema.into(), value)
+      }
+      ErrorHeaderValue::With(_) => header(
+        error_header.description.to_owned(),
+        ctx.schema.subschema_for::<String>(),
+        value,
+      ),
+    };
+    // built from each response, which may leave it out
+    header.required = matches!(error_header.value, ErrorHeaderValue::Fixed(_));
+    (error_header.name.to_owned(), header)
+  });
+
+  [
+    ("Content-Language".to_owned(), content_language),
+    ("Request-Id".to_owned(), request_id),
+  ]
+  .into_iter()
+  .chain(headers)
+  .map(|(name, header)| (name, aide::openapi::ReferenceOr::Item(header)))
+  .collect()
+}
+
+fn header(
+  description: String,
+  json_schema: schemars::schema::Schema,
+  example: Option<String>,
+) -> aide::openapi::Header {
+  aide::openapi::Header {
+    description: Some(description),
+    style: aide::openapi::HeaderStyle::Simple,
+    required: true,
+    deprecated: None,
+    format: aide::openapi::ParameterSchemaOrContent::Schema(aide::openapi::SchemaObject {
+      json_schema,
+      external_docs: None,
+      example: example.map(Into::into),
+    }),
+    example: None,
+    examples: Default::default(),
+    extensions: Default::default(),
+  }
+}
+
+fn app_error_media_type<T: JsonSchema + serde::Serialize>(
+  ctx: &mut aide::gen::GenContext,
+  status: AppErrorCode,
+  example: T,
+) -> aide::openapi::MediaType {
+  let schema = ctx.schema.subschema_for::<T>().into_object();
+
+  aide::openapi::MediaType {
+    schema: Some(aide::openapi::SchemaObject {
+      json_schema: schema.into(),
+      example: None,
+      external_docs: None,
+    }),
+    // named by code, so examples of codes sharing a status can be told apart once merged
+    examples: indexmap::IndexMap::from_iter([(
+      status.as_str().to_owned(),
+      aide::openapi::ReferenceOr::Item(aide::openapi::Example {
+        summary: Some(status.description().to_owned()),
+        value: Some(unsafe { serde_json::to_value(example).unwrap_unchecked() }),
+        ..Default::default()
+      }),
+    )]),
+    ..Default::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use aide::openapi::{Operation, ReferenceOr, Response, StatusCode};
+
+  use super::*;
+
+  fn response(operation: &Operation, status: u16) -> Option<&Response> {
+    let responses = operation.responses.as_ref()?;
+    match responses.responses.get(&StatusCode::Code(status))? {
+      ReferenceOr::Item(response) => Some(response),
+      ReferenceOr::Reference { .. } => None,
+    }
+  }
+
+  /// The example names of each media type of the response.
+  fn examples(response: &Response) -> Vec<Vec<&str>> {
+    response
+      .content
+      .values()
+      .map(|media_type| media_type.examples.keys().map(String::as_str).collect())
+      .collect()
+  }
+
+  fn documented(codes: &[AppErrorCode]) -> Operation {
+    let mut operation = Operation::default();
+    aide::gen::in_context(|ctx| set_inferred_responses(ctx, &mut operation, codes.to_vec()));
+    operation
+  }
+
+  #[test]
+  fn merges_codes_sharing_a_status() {
+    use AppErrorCode::{JsonSyntaxError, PathRejection};
+
+    let operation = documented(&[PathRejection, JsonSyntaxError, PathRejection]);
+    let response = response(&operation, 400).unwrap();
+    assert_eq!(
+      response.description,
+      format!(
+        "{}\n\n{}",
+        PathRejection.description(),
+        JsonSyntaxError.description()
+      )
+    );
+    assert_eq!(
+      examples(response),
+      [["PATH_REJECTION", "JSON_SYNTAX_ERROR"]; 2]
+    );
+  }
+
+  #[test]
+  fn unmerges_one_code_of_a_shared_status() {
+    use AppErrorCode::{JsonSyntaxError, NotFound, PathRejection};
+
+    let mut operation = documented(&[PathRejection, JsonSyntaxError]);
+    assert!(unset_inferred_response(&mut operation, JsonSyntaxError));
+    assert!(!unset_inferred_response(&mut operation, JsonSyntaxError));
+    assert!(!unset_inferred_response(&mut operation, NotFound));
+
+    let response = response(&operation, 400).unwrap();
+    assert_eq!(response.description, PathRejection.description());
+    assert_eq!(examples(response), [["PATH_REJECTION"]; 2]);
+    assert_eq!(operation, documented(&[PathRejection]));
+  }
+
+  #[test]
+  fn keeps_what_the_remaining_codes_share() {
+    use AppErrorCode::{AuthKeyInvalid, AuthKeyMissing};
+
+    let mut operation = documented(&[AuthKeyMissing, AuthKeyInvalid]);
+    assert!(unset_inferred_response(&mut operation, AuthKeyMissing));
+    let response = response(&operation, 401).unwrap();
+    assert_eq!(response.description, AuthKeyInvalid.description());
+    assert!(response.headers.contains_key("WWW-Authenticate"));
+    assert_eq!(examples(response), [["AUTH_KEY_INVALID"]; 2]);
+  }
+
+  #[test]
+  fn removes_the_response_along_with_its_last_code() {
+    use AppErrorCode::{NotFound, PathRejection};
+
+    let mut operation = documented(&[PathRejection, NotFound]);
+    assert!(unset_inferred_response(&mut operation, PathRejection));
+    assert!(response(&operation, 400).is_none());
+    assert_eq!(operation, documented(&[NotFound]));
+  }
+}
@@ -0,0 +1,48 @@
+use std::ops::Deref;
+
+use axum::extract::{FromRequest, Request};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  error::AppError,
+  multipart::{extract_form, form_error_codes, set_form_body, MultipartForm},
+};
+
+use super::set_inferred_responses;
+
+/// Deserialize a `multipart/form-data` body, enforce the `FILE_RULES` of its files, and apply
+/// validation checks
+pub struct MultipartValidate<T>(pub T);
+
+impl<T> Deref for MultipartValidate<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for MultipartValidate<T>
+where
+  T: DeserializeOwned + Validate + MultipartForm + JsonSchema,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = extract_form(req, state).await;
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
+  }
+}
+
+impl<T: MultipartForm + JsonSchema> aide::OperationInput for MultipartValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_inferred_responses(ctx, operation, form_error_codes::<T>());
+    set_form_body::<T>(ctx, operation);
+  }
+}
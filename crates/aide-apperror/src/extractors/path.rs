@@ -1,15 +1,28 @@
+use std::ops::Deref;
+
 use axum::{extract::FromRequestParts, http::request::Parts};
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
-use crate::error::{AppError, AppErrorCode};
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+};
 
-use super::set_inferred_response;
+use super::{set_inferred_response, validated::validate};
 
 /// Extract Path variables, and apply validation checks
 pub struct PathValidate<T>(pub T);
 
+impl<T> Deref for PathValidate<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
 #[axum::async_trait]
 impl<T, S> FromRequestParts<S> for PathValidate<T>
 where
@@ -19,11 +32,10 @@ where
   type Rejection = AppError;
 
   async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-    let value: T = axum::extract::Path::from_request_parts(parts, state)
-      .await?
-      .0;
-    value.validate()?;
-    Ok(Self(value))
+    use axum::extract::Path;
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(Path::from_request_parts(parts, state).await).map(|v: Path<T>| v.0);
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
   }
 }
 
@@ -41,7 +53,6 @@ impl<T: JsonSchema> aide::OperationInput for PathValidate<T> {
   //   ctx: &mut aide::gen::GenContext,
   //   operation: &mut aide::openapi::Operation,
   // ) -> Vec<(Option<u16>, aide::openapi::Response)> {
-  //   println!("hmmm path_val early");
   //   <axum::extract::Path<T> as aide::OperationInput>::inferred_early_responses(ctx, operation)
   // }
 }
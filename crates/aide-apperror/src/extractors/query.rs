@@ -0,0 +1,52 @@
+use std::ops::Deref;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+};
+
+use super::{set_inferred_responses, validated::validate};
+
+/// Extract the query string, and apply validation checks
+pub struct QueryValidate<T>(pub T);
+
+impl<T> Deref for QueryValidate<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+#[axum::async_trait]
+impl<T, S> FromRequestParts<S> for QueryValidate<T>
+where
+  T: DeserializeOwned + Validate + JsonSchema + Send,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    use axum::extract::Query;
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(Query::from_request_parts(parts, state).await).map(|v: Query<T>| v.0);
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
+  }
+}
+
+/// Documents the parameters of `T`, along with their validation rules.
+impl<T: JsonSchema> aide::OperationInput for QueryValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_inferred_responses(
+      ctx,
+      operation,
+      [AppErrorCode::Validation, AppErrorCode::QueryRejection],
+    );
+    <axum::extract::Query<T> as aide::OperationInput>::operation_input(ctx, operation)
+  }
+}
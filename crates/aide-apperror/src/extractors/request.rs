@@ -0,0 +1,180 @@
+use std::ops::Deref;
+
+use aide::operation::{add_parameters, parameters_from_schema, ParamLocation};
+use axum::{
+  extract::{FromRequest, FromRequestParts, Request},
+  http::{request::Parts, HeaderMap},
+};
+use schemars::JsonSchema;
+use serde::{
+  de::{self, DeserializeOwned, Visitor},
+  forward_to_deserialize_any, Deserializer,
+};
+use validator::{Validate, ValidationErrors};
+
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+  multipart::FormDeserializer,
+};
+
+use super::{set_inferred_responses, RejectionCodes};
+
+/// A struct assembled from several parts of a request, see `#[derive(RequestSources)]`.
+#[axum::async_trait]
+pub trait RequestSources: Sized {
+  /// Extract every field from its source.
+  async fn from_sources<S: Send + Sync>(req: Request, state: &S) -> Result<Self, AppError>;
+
+  /// The codes the rejections of the sources are converted into.
+  fn rejection_codes() -> Vec<AppErrorCode>;
+
+  /// Name the fields of `errors` as they are deserialized from their source, header fields by
+  /// their header, see `serde_field_names`.
+  fn serde_field_names(errors: ValidationErrors) -> ValidationErrors;
+
+  /// Document every source, as parameters or as the body.
+  fn document_sources(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation);
+}
+
+/// Extract `T` from the path, query, headers and body of the request, and apply validation checks
+/// to the assembled struct
+///
+/// ```ignore
+/// #[derive(RequestSources, Validate)]
+/// struct RenameTodo {
+///   #[from(path)]
+///   id: Uuid,
+///   #[from(body)]
+///   #[validate(length(min = 5))]
+///   description: String,
+/// }
+/// ```
+pub struct RequestValidate<T>(pub T);
+
+impl<T> Deref for RequestValidate<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+/// Extract `T` from its sources, and apply validation checks.
+pub(crate) async fn extract_request<T, S>(req: Request, state: &S) -> Result<T, AppError>
+where
+  T: RequestSources + Validate,
+  S: Send + Sync,
+{
+  let request = T::from_sources(req, state).await?;
+  request.validate().map_err(T::serde_field_names)?;
+  Ok(request)
+}
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for RequestValidate<T>
+where
+  T: RequestSources + Validate,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = extract_request(req, state).await;
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
+  }
+}
+
+impl<T: RequestSources> aide::OperationInput for RequestValidate<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    let codes = T::rejection_codes();
+    set_inferred_responses(
+      ctx,
+      operation,
+      [AppErrorCode::Validation].into_iter().chain(codes),
+    );
+    T::document_sources(ctx, operation);
+  }
+}
+
+/// Deserialize headers into the fields of `T`, by header name
+///
+/// Values are parsed like those of a query string, repeated headers deserialize into sequences.
+pub struct HeaderFields<T>(pub T);
+
+impl<T> Deref for HeaderFields<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<T> RejectionCodes for HeaderFields<T> {
+  const REJECTION_CODES: &'static [AppErrorCode] = &[AppErrorCode::HeaderRejection];
+}
+
+#[axum::async_trait]
+impl<T, S> FromRequestParts<S> for HeaderFields<T>
+where
+  T: DeserializeOwned,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    T::deserialize(HeadersDeserializer(&parts.headers))
+      .map(Self)
+      .map_err(AppError::HeaderRejection)
+  }
+}
+
+/// Documents the fields of `T` as header parameters.
+impl<T: JsonSchema> aide::OperationInput for HeaderFields<T> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    let schema = ctx.schema.subschema_for::<T>().into_object();
+    let params = parameters_from_schema(ctx, schema, ParamLocation::Header);
+    add_parameters(ctx, operation, params);
+  }
+}
+
+/// Collects the headers named after the fields of the struct, as a form of text fields.
+struct HeadersDeserializer<'a>(&'a HeaderMap);
+
+impl<'de> Deserializer<'de> for HeadersDeserializer<'_> {
+  type Error = de::value::Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+    Err(de::Error::custom(
+      "headers can only be deserialized into a struct",
+    ))
+  }
+
+  fn deserialize_struct<V: Visitor<'de>>(
+    self,
+    name: &'static str,
+    fields: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Self::Error> {
+    let mut form = Vec::new();
+    for field in fields {
+      let values = self
+        .0
+        .get_all(*field)
+        .iter()
+        .map(|value| value.to_str().map(str::to_owned))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| de::Error::custom(format!("`{field}` is not a visible ASCII string")))?;
+      if !values.is_empty() {
+        form.push((field.to_string(), values));
+      }
+    }
+    FormDeserializer::text(form).deserialize_struct(name, fields, visitor)
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+    unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+  }
+}
@@ -0,0 +1,116 @@
+use std::ops::Deref;
+
+use axum::{
+  extract::{FromRequest, FromRequestParts, Path, Query, Request},
+  http::request::Parts,
+  Form, Json,
+};
+use schemars::JsonSchema;
+use validator::Validate;
+
+use crate::{
+  debug::ErrorOrigin,
+  details::serde_field_names,
+  error::{AppError, AppErrorCode},
+};
+
+use super::set_inferred_responses;
+
+/// Run extractor `E`, and apply validation checks to what it extracted,
+/// e.g. `Validated<Json<T>>` or `Validated<Form<T>>`
+///
+/// `E` must implement `RejectionCodes` to be documented, which `Json`, `Form`, `Path` and `Query` do.
+pub struct Validated<E>(pub E);
+
+/// The codes the rejection of an extractor is converted into, documented by `Validated`.
+///
+/// Implement it for other extractors whose rejection converts into `AppError`.
+pub trait RejectionCodes {
+  const REJECTION_CODES: &'static [AppErrorCode];
+}
+
+impl<T> RejectionCodes for Json<T> {
+  const REJECTION_CODES: &'static [AppErrorCode] = &[
+    AppErrorCode::JsonDataError,
+    AppErrorCode::JsonSyntaxError,
+    AppErrorCode::MissingJsonContentType,
+    AppErrorCode::JsonPayloadTooLarge,
+  ];
+}
+
+impl<T> RejectionCodes for Form<T> {
+  const REJECTION_CODES: &'static [AppErrorCode] = &[
+    AppErrorCode::FormDataError,
+    AppErrorCode::FormRejection,
+    AppErrorCode::MissingFormContentType,
+    AppErrorCode::FormPayloadTooLarge,
+  ];
+}
+
+impl<T> RejectionCodes for Path<T> {
+  const REJECTION_CODES: &'static [AppErrorCode] = &[AppErrorCode::PathRejection];
+}
+
+impl<T> RejectionCodes for Query<T> {
+  const REJECTION_CODES: &'static [AppErrorCode] = &[AppErrorCode::QueryRejection];
+}
+
+/// Convert the rejection of an extractor into an `AppError`, then validate what it extracted.
+pub(crate) fn validate<E, R>(extracted: Result<E, R>) -> Result<E, AppError>
+where
+  E: Deref,
+  E::Target: Validate + JsonSchema,
+  AppError: From<R>,
+{
+  let extracted = extracted?;
+  extracted
+    .validate()
+    .map_err(serde_field_names::<E::Target>)?;
+  Ok(extracted)
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequestParts<S> for Validated<E>
+where
+  E: FromRequestParts<S> + Deref,
+  E::Target: Validate + JsonSchema,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = validate(E::from_request_parts(parts, state).await);
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
+  }
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequest<S> for Validated<E>
+where
+  E: FromRequest<S> + Deref,
+  E::Target: Validate + JsonSchema,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = validate(E::from_request(req, state).await);
+    ErrorOrigin::track::<Self, _, _>(origin, value).map(Self)
+  }
+}
+
+impl<E: aide::OperationInput + RejectionCodes> aide::OperationInput for Validated<E> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    let codes = E::REJECTION_CODES.iter().copied();
+    set_inferred_responses(
+      ctx,
+      operation,
+      [AppErrorCode::Validation].into_iter().chain(codes),
+    );
+    E::operation_input(ctx, operation)
+  }
+}
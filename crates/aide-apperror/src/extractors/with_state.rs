@@ -0,0 +1,80 @@
+use std::ops::Deref;
+
+use axum::{
+  extract::{FromRequest, FromRequestParts, Request},
+  http::request::Parts,
+};
+
+use crate::{
+  debug::ErrorOrigin,
+  error::{AppError, AppErrorCode},
+  validate::ValidateWithState,
+};
+
+use super::set_inferred_response;
+
+/// Run extractor `E`, then the `ValidateWithState` checks of what it extracted,
+/// e.g. `ValidatedWithState<JsonValidate<T>>` or `ValidatedWithState<RequestValidate<T>>`
+pub struct ValidatedWithState<E>(pub E);
+
+/// Run the `ValidateWithState` checks of `extracted`, noting `X` as the origin of failures.
+pub(crate) async fn validate_with_state<X, E, S>(
+  origin: Option<ErrorOrigin>,
+  extracted: E,
+  state: &S,
+) -> Result<E, AppError>
+where
+  E: Deref + Send,
+  E::Target: ValidateWithState<S>,
+  S: Sync,
+{
+  let checked = extracted.validate_with_state(state).await;
+  ErrorOrigin::track::<X, _, _>(origin, checked)?;
+  Ok(extracted)
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequestParts<S> for ValidatedWithState<E>
+where
+  E: FromRequestParts<S> + Deref + Send,
+  E::Target: ValidateWithState<S>,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(&parts.extensions);
+    let value = E::from_request_parts(parts, state).await?;
+    validate_with_state::<Self, _, _>(origin, value, state)
+      .await
+      .map(Self)
+  }
+}
+
+#[axum::async_trait]
+impl<E, S> FromRequest<S> for ValidatedWithState<E>
+where
+  E: FromRequest<S> + Deref + Send,
+  E::Target: ValidateWithState<S>,
+  AppError: From<E::Rejection>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
+
+  async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+    let origin = ErrorOrigin::of(req.extensions());
+    let value = E::from_request(req, state).await?;
+    validate_with_state::<Self, _, _>(origin, value, state)
+      .await
+      .map(Self)
+  }
+}
+
+/// Documents what `E` documents, along with the `Validation` response of the checks.
+impl<E: aide::OperationInput> aide::OperationInput for ValidatedWithState<E> {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    set_inferred_response(ctx, operation, AppErrorCode::Validation);
+    E::operation_input(ctx, operation)
+  }
+}
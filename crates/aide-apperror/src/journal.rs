@@ -0,0 +1,507 @@
+use std::{
+  collections::{BTreeMap, VecDeque},
+  fs::{self, File, OpenOptions},
+  io::{self, BufRead, BufReader, BufWriter, Write},
+  path::{Path, PathBuf},
+  sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Mutex,
+  },
+  thread::{self, JoinHandle},
+};
+
+use axum::{
+  extract::{MatchedPath, Request, State},
+  http::{header, HeaderName, Uri},
+  middleware::Next,
+  response::Response,
+};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+  error::{AppErrorCode, AppErrorOutput, ErrorSourceChain},
+  policy::RemappedFrom,
+};
+
+/// Everything known about an error response, looked up by its `error_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorRecord {
+  /// The `error_id` that was sent to the client.
+  pub error_id: Uuid,
+  /// When the error response was sent.
+  pub timestamp: DateTime<Utc>,
+  /// The error code that was sent to the client.
+  pub code: AppErrorCode,
+  /// The error code before the `ErrorPolicy` remapped it into `code`, absent if it did not.
+  pub original_code: Option<AppErrorCode>,
+  /// The HTTP status code that was sent to the client.
+  pub status: u16,
+  /// The error message that was sent to the client.
+  pub error: String,
+  /// The messages of the errors that caused it, outermost first. Never sent to the client.
+  pub source_chain: Vec<String>,
+  /// The route that handled the request, e.g. `/todo/:id`, or its path when that is unknown.
+  pub route: String,
+  /// The request that failed.
+  pub request: RequestMetadata,
+}
+
+/// The request an `ErrorRecord` was made for.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RequestMetadata {
+  pub method: String,
+  /// The request URI, without its query string unless the `Redaction` keeps it.
+  pub uri: String,
+  /// Request headers, without those of the `Redaction`.
+  pub headers: BTreeMap<String, String>,
+}
+
+/// What the `ErrorJournal` leaves out of the requests it records.
+///
+/// By default the credential headers of HTTP, and the query string, which may carry tokens.
+#[derive(Debug, Clone)]
+pub struct Redaction {
+  headers: Vec<HeaderName>,
+  keep_query: bool,
+}
+
+impl Default for Redaction {
+  fn default() -> Self {
+    Self {
+      headers: vec![
+        header::AUTHORIZATION,
+        header::PROXY_AUTHORIZATION,
+        header::COOKIE,
+        header::SET_COOKIE,
+      ],
+      keep_query: false,
+    }
+  }
+}
+
+impl Redaction {
+  /// Also leave out `header`, e.g. the API key header of the app.
+  pub fn header(mut self, header: HeaderName) -> Self {
+    self.headers.push(header);
+    self
+  }
+
+  /// Record the query string of requests.
+  pub fn keep_query(mut self) -> Self {
+    self.keep_query = true;
+    self
+  }
+
+  fn request(&self, request: &Request) -> RequestMetadata {
+    let uri = request.uri();
+    let uri = match uri.path_and_query() {
+      Some(path) if !self.keep_query && path.query().is_some() => {
+        let mut parts = uri.clone().into_parts();
+        parts.path_and_query = Some(
+          path
+            .path()
+            .parse()
+            .expect("a path is a valid path_and_query"),
+        );
+        Uri::from_parts(parts).expect("only the query changed")
+      }
+      _ => uri.clone(),
+    };
+    RequestMetadata {
+      method: request.method().to_string(),
+      uri: uri.to_string(),
+      headers: request
+        .headers()
+        .iter()
+        .filter(|(name, _)| !self.headers.contains(name))
+        .map(|(name, value)| {
+          let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+          (name.to_string(), value)
+        })
+        .collect(),
+    }
+  }
+}
+
+/// A bounded journal of the latest error responses, optionally persisted as json lines.
+///
+/// Records are written to the file by a background thread, recording never waits for the disk.
+/// Dropping the last clone of the journal waits for the records still being written.
+#[derive(Debug, Clone)]
+pub struct ErrorJournal(Arc<JournalInner>);
+
+#[derive(Debug)]
+struct JournalInner {
+  capacity: usize,
+  redaction: Redaction,
+  records: Mutex<VecDeque<ErrorRecord>>,
+  /// Sends new records to the writer thread, if persisted.
+  writer: Option<Sender<ErrorRecord>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for JournalInner {
+  fn drop(&mut self) {
+    // the writer thread stops once the channel is closed
+    self.writer.take();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+/// What the writer thread knows of the file.
+#[derive(Debug, Default)]
+struct JournalState {
+  records: VecDeque<ErrorRecord>,
+  /// Lines in the file, it is compacted once this gets too far ahead of `records`.
+  file_lines: usize,
+}
+
+impl Default for ErrorJournal {
+  fn default() -> Self {
+    Self::in_memory(1000, Redaction::default())
+  }
+}
+
+impl ErrorJournal {
+  /// Keep the latest `capacity` records in memory.
+  ///
+  /// Panics if `capacity` is 0.
+  pub fn in_memory(capacity: usize, redaction: Redaction) -> Self {
+    assert!(
+      capacity > 0,
+      "the error journal needs a capacity of at least 1"
+    );
+    Self(Arc::new(JournalInner {
+      capacity,
+      redaction,
+      records: Default::default(),
+      writer: None,
+      thread: None,
+    }))
+  }
+
+  /// Load the latest `capacity` records of the journal at `path`, creating it if missing.
+  ///
+  /// Panics if `capacity` is 0.
+  pub fn open(path: impl Into<PathBuf>, capacity: usize, redaction: Redaction) -> io::Result<Self> {
+    assert!(
+      capacity > 0,
+      "the error journal needs a capacity of at least 1"
+    );
+    let path = path.into();
+    let mut records = VecDeque::with_capacity(capacity);
+    if path.exists() {
+      for line in BufReader::new(File::open(&path)?).lines() {
+        // skip lines that do not parse, e.g. written by an older version
+        let Ok(record) = serde_json::from_str(&line?) else {
+          continue;
+        };
+        if records.len() == capacity {
+          records.pop_front();
+        }
+        records.push_back(record);
+      }
+    }
+
+    let mut state = JournalState {
+      records: records.clone(),
+      file_lines: 0,
+    };
+    write_all(&path, &mut state)?;
+
+    let (writer, receiver) = mpsc::channel();
+    let thread = thread::Builder::new()
+      .name("error-journal".into())
+      .spawn(move || write_records(&path, capacity, state, receiver))?;
+
+    Ok(Self(Arc::new(JournalInner {
+      capacity,
+      redaction,
+      records: Mutex::new(records),
+      writer: Some(writer),
+      thread: Some(thread),
+    })))
+  }
+
+  pub fn record(&self, record: ErrorRecord) {
+    let JournalInner {
+      capacity,
+      records,
+      writer,
+      ..
+    } = &*self.0;
+    {
+      let mut records = records.lock().unwrap_or_else(|e| e.into_inner());
+      if records.len() == *capacity {
+        records.pop_front();
+      }
+      records.push_back(record.clone());
+    }
+
+    if let Some(writer) = writer {
+      // the writer only stops when the journal is dropped
+      let _ = writer.send(record);
+    }
+  }
+
+  pub fn get(&self, error_id: Uuid) -> Option<ErrorRecord> {
+    let records = self.0.records.lock().unwrap_or_else(|e| e.into_inner());
+    records
+      .iter()
+      .rev()
+      .find(|r| r.error_id == error_id)
+      .cloned()
+  }
+}
+
+/// Persist the records sent by the journal, until it is dropped.
+fn write_records(
+  path: &Path,
+  capacity: usize,
+  mut state: JournalState,
+  receiver: Receiver<ErrorRecord>,
+) {
+  for record in receiver {
+    if state.records.len() == capacity {
+      state.records.pop_front();
+    }
+    state.records.push_back(record.clone());
+
+    let written = if state.file_lines >= capacity * 2 {
+      write_all(path, &mut state)
+    } else {
+      append(path, &record).map(|_| state.file_lines += 1)
+    };
+    if let Err(e) = written {
+      tracing::error!(error_id = %record.error_id, "failed to persist error record: {e}");
+    }
+  }
+}
+
+fn append(path: &Path, record: &ErrorRecord) -> io::Result<()> {
+  let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+  writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+/// Replace the journal with `state.records` at once, a failed write leaves the previous one.
+fn write_all(path: &Path, state: &mut JournalState) -> io::Result<()> {
+  let mut tmp_path = path.as_os_str().to_owned();
+  tmp_path.push(".tmp");
+  let mut file = BufWriter::new(File::create(&tmp_path)?);
+  for record in &state.records {
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+  }
+  file.flush()?;
+  file.get_ref().sync_all()?;
+  fs::rename(&tmp_path, path)?;
+  state.file_lines = state.records.len();
+  Ok(())
+}
+
+/// Middleware that records every `AppError` response in the `ErrorJournal`.
+///
+/// The route is only known to layers added after routing, add `route_matched_path` to the routers
+/// so that it can be recorded.
+///
+/// ```ignore
+/// router
+///   .route_layer(axum::middleware::from_fn(route_matched_path))
+///   .layer(axum::middleware::from_fn_with_state(journal, record_errors))
+/// ```
+pub async fn record_errors(
+  State(journal): State<ErrorJournal>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let matched_path = request.extensions().get::<MatchedPath>().cloned();
+  let path = request.uri().path().to_owned();
+  let metadata = journal.0.redaction.request(&request);
+
+  let response = next.run(request).await;
+
+  if let Some(output) = response.extensions().get::<AppErrorOutput>() {
+    let route = match response
+      .extensions()
+      .get::<MatchedPath>()
+      .or(matched_path.as_ref())
+    {
+      Some(matched_path) => matched_path.as_str().to_owned(),
+      None => path,
+    };
+    journal.record(ErrorRecord {
+      error_id: output.error_id,
+      timestamp: Utc::now(),
+      code: output.code,
+      original_code: response
+        .extensions()
+        .get::<RemappedFrom>()
+        .map(|original| original.0),
+      status: response.status().as_u16(),
+      error: output.error.clone(),
+      source_chain: response
+        .extensions()
+        .get::<ErrorSourceChain>()
+        .map(|chain| chain.0.clone())
+        .unwrap_or_default(),
+      route,
+      request: metadata,
+    });
+  }
+  response
+}
+
+/// Route layer passing the `MatchedPath` of the request on to the response, for `record_errors`.
+pub async fn route_matched_path(request: Request, next: Next) -> Response {
+  let matched_path = request.extensions().get::<MatchedPath>().cloned();
+  let mut response = next.run(request).await;
+  if let Some(matched_path) = matched_path {
+    response.extensions_mut().insert(matched_path);
+  }
+  response
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::body::Body;
+
+  use super::*;
+
+  fn error_record() -> ErrorRecord {
+    ErrorRecord {
+      error_id: Uuid::new_v4(),
+      timestamp: Utc::now(),
+      code: AppErrorCode::NotFound,
+      original_code: None,
+      status: 404,
+      error: "Not Found".into(),
+      source_chain: Vec::new(),
+      route: "/todo/:id".into(),
+      request: RequestMetadata {
+        method: "GET".into(),
+        uri: "/todo/1".into(),
+        headers: BTreeMap::new(),
+      },
+    }
+  }
+
+  /// A journal file that is removed once the test is done.
+  struct TempJournal(PathBuf);
+
+  impl TempJournal {
+    fn new() -> Self {
+      Self(std::env::temp_dir().join(format!("error-journal-{}.jsonl", Uuid::new_v4())))
+    }
+
+    fn open(&self, capacity: usize) -> ErrorJournal {
+      ErrorJournal::open(&self.0, capacity, Redaction::default()).unwrap()
+    }
+
+    fn lines(&self) -> usize {
+      fs::read_to_string(&self.0).unwrap().lines().count()
+    }
+  }
+
+  impl Drop for TempJournal {
+    fn drop(&mut self) {
+      let _ = fs::remove_file(&self.0);
+    }
+  }
+
+  #[test]
+  fn looks_up_records_by_error_id() {
+    let journal = ErrorJournal::in_memory(10, Redaction::default());
+    let record = error_record();
+    journal.record(record.clone());
+    journal.record(error_record());
+
+    let found = journal.get(record.error_id).unwrap();
+    assert_eq!(found.error_id, record.error_id);
+    assert!(journal.get(Uuid::new_v4()).is_none());
+  }
+
+  #[test]
+  fn evicts_the_oldest_records_at_capacity() {
+    let journal = ErrorJournal::in_memory(2, Redaction::default());
+    let records: Vec<_> = (0..3).map(|_| error_record()).collect();
+    records.iter().for_each(|r| journal.record(r.clone()));
+
+    assert!(journal.get(records[0].error_id).is_none());
+    assert!(journal.get(records[1].error_id).is_some());
+    assert!(journal.get(records[2].error_id).is_some());
+  }
+
+  #[test]
+  #[should_panic(expected = "capacity of at least 1")]
+  fn rejects_a_capacity_of_zero() {
+    ErrorJournal::in_memory(0, Redaction::default());
+  }
+
+  #[test]
+  fn reloads_the_latest_records() {
+    let file = TempJournal::new();
+    let records: Vec<_> = (0..3).map(|_| error_record()).collect();
+    let journal = file.open(10);
+    records.iter().for_each(|r| journal.record(r.clone()));
+    drop(journal);
+
+    let journal = file.open(2);
+    assert!(journal.get(records[0].error_id).is_none());
+    assert_eq!(
+      journal.get(records[2].error_id).unwrap().route,
+      records[2].route
+    );
+    // compacted to `capacity` when opened
+    assert_eq!(file.lines(), 2);
+  }
+
+  #[test]
+  fn compacts_the_file_at_twice_the_capacity() {
+    let file = TempJournal::new();
+    let journal = file.open(2);
+    (0..4).for_each(|_| journal.record(error_record()));
+    drop(journal);
+    assert_eq!(file.lines(), 4);
+
+    // opened with 2 lines, the third record reaches 4 and rewrites the latest 2
+    let records: Vec<_> = (0..3).map(|_| error_record()).collect();
+    let journal = file.open(2);
+    records.iter().for_each(|r| journal.record(r.clone()));
+    drop(journal);
+    assert_eq!(file.lines(), 2);
+
+    let journal = file.open(2);
+    assert!(journal.get(records[0].error_id).is_none());
+    assert!(journal.get(records[1].error_id).is_some());
+    assert!(journal.get(records[2].error_id).is_some());
+  }
+
+  #[test]
+  fn redacts_credentials_and_the_query_string() {
+    let request = Request::builder()
+      .uri("/todo?token=secret")
+      .header(header::AUTHORIZATION, "Bearer secret")
+      .header(header::COOKIE, "session=secret")
+      .header("x-auth-key", "secret")
+      .header("x-request-tag", "kept")
+      .body(Body::empty())
+      .unwrap();
+
+    let metadata = Redaction::default()
+      .header(HeaderName::from_static("x-auth-key"))
+      .request(&request);
+    assert_eq!(metadata.uri, "/todo");
+    assert_eq!(
+      metadata.headers.keys().collect::<Vec<_>>(),
+      ["x-request-tag"]
+    );
+
+    let metadata = Redaction::default().keep_query().request(&request);
+    assert_eq!(metadata.uri, "/todo?token=secret");
+    assert!(metadata.headers.contains_key("x-auth-key"));
+  }
+}
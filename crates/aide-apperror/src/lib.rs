@@ -0,0 +1,35 @@
+// Lets `#[derive(ApiError)]` refer to `::aide_apperror` from within this crate too.
+extern crate self as aide_apperror;
+
+pub mod catalog;
+pub mod debug;
+pub mod details;
+pub mod error;
+pub mod error_set;
+pub mod extractors;
+pub mod journal;
+pub mod locale;
+pub mod multipart;
+pub mod panic;
+pub mod policy;
+pub mod problem;
+pub mod validate;
+
+pub use aide_apperror_macros::{ApiError, RequestSources};
+pub use catalog::{
+  error_catalog, error_reference_html, error_reference_markdown, ErrorCatalogEntry,
+};
+pub use debug::{debug_errors, debug_mode, set_debug_mode, ErrorOrigin};
+pub use details::{ErrorDetails, FieldError};
+pub use error::{
+  codes, AppError, AppErrorCode, AppErrorOutput, BoxError, ErrorHeader, ErrorHeaderValue,
+};
+pub use error_set::{ApiErrors, ErrorCodeMarker, ErrorSet};
+pub use extractors::{set_inferred_response, set_inferred_responses};
+pub use journal::{record_errors, route_matched_path, ErrorJournal, ErrorRecord, Redaction};
+pub use locale::{localize_errors, AcceptLanguage, MessageCatalog, Messages};
+pub use multipart::{FileRule, MultipartForm, UploadedFile};
+pub use panic::{catch_panic, document_panics};
+pub use policy::{apply_error_policy, ErrorPolicy};
+pub use problem::{error_format, ErrorFormat, ProblemDetails};
+pub use validate::ValidateWithState;
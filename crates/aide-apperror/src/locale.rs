@@ -0,0 +1,354 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  convert::Infallible,
+  sync::Arc,
+};
+
+use aide::{
+  openapi::{HeaderStyle, Parameter, ParameterData, ParameterSchemaOrContent, SchemaObject},
+  operation::add_parameters,
+};
+use axum::{
+  extract::{FromRequestParts, Request, State},
+  http::{header, request::Parts, HeaderMap, HeaderValue},
+  middleware::Next,
+  response::Response,
+};
+use serde_json::Value;
+
+use crate::{
+  error::{AppErrorCode, AppErrorOutput},
+  problem::rerender,
+};
+
+/// The language of the messages built into `AppError`, and of the documented examples.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The language tags of `Accept-Language`, lowercased and most preferred first.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptLanguage(pub Vec<String>);
+
+impl AcceptLanguage {
+  pub fn from_headers(headers: &HeaderMap) -> Self {
+    let mut tags: Vec<(String, f32)> = headers
+      .get_all(header::ACCEPT_LANGUAGE)
+      .iter()
+      .filter_map(|v| v.to_str().ok())
+      .flat_map(|v| v.split(','))
+      .filter_map(|range| {
+        let mut parts = range.split(';').map(str::trim);
+        let tag = parts.next()?.to_ascii_lowercase();
+        let q = parts
+          .find_map(|p| p.strip_prefix("q="))
+          .map_or(Some(1.0), |q| q.parse().ok())?;
+        (!tag.is_empty() && tag != "*" && q > 0.0).then_some((tag, q))
+      })
+      .collect();
+    // stable, so equally weighted tags keep their order
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Self(tags.into_iter().map(|(tag, _)| tag).collect())
+  }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for AcceptLanguage
+where
+  S: Send + Sync,
+{
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    Ok(Self::from_headers(&parts.headers))
+  }
+}
+
+impl aide::OperationInput for AcceptLanguage {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    let schema = ctx.schema.subschema_for::<String>();
+    add_parameters(
+      ctx,
+      operation,
+      [Parameter::Header {
+        parameter_data: ParameterData {
+          name: "Accept-Language".to_owned(),
+          description: Some("The preferred languages of error messages.".to_owned()),
+          required: false,
+          deprecated: None,
+          format: ParameterSchemaOrContent::Schema(SchemaObject {
+            json_schema: schema,
+            external_docs: None,
+            example: None,
+          }),
+          example: None,
+          examples: Default::default(),
+          explode: None,
+          extensions: Default::default(),
+        },
+        style: HeaderStyle::Simple,
+      }],
+    );
+  }
+}
+
+/// The translations of one language.
+#[derive(Debug, Clone, Default)]
+pub struct Messages {
+  codes: HashMap<AppErrorCode, String>,
+  rules: HashMap<String, String>,
+}
+
+impl Messages {
+  /// Translate the `error` of `code`.
+  pub fn code(mut self, code: AppErrorCode, message: impl Into<String>) -> Self {
+    self.codes.insert(code, message.into());
+    self
+  }
+
+  /// Translate the `message` of validation errors failing `rule`, e.g. `length`.
+  ///
+  /// `{name}` is replaced by the `name` param of the rule, e.g. `{min}`. When the error has no such
+  /// param, e.g. a `length` with only a `max`, it keeps its untranslated message.
+  pub fn rule(mut self, rule: impl Into<String>, message: impl Into<String>) -> Self {
+    self.rules.insert(rule.into(), message.into());
+    self
+  }
+
+  fn rule_message(&self, rule: &str, params: Option<&Value>) -> Option<String> {
+    let mut template = self.rules.get(rule)?.as_str();
+    let params = params.and_then(Value::as_object);
+    let mut message = String::with_capacity(template.len());
+    while let Some(start) = template.find('{') {
+      let Some(end) = template[start..].find('}').map(|end| start + end) else {
+        break;
+      };
+      let value = params?.get(&template[start + 1..end])?;
+      message.push_str(&template[..start]);
+      message.push_str(&param_text(value));
+      template = &template[end + 1..];
+    }
+    message.push_str(template);
+    Some(message)
+  }
+}
+
+/// The text of a rule param. `validator` stores `range` bounds as floats, whole numbers are
+/// written without their fraction, `1` rather than `1.0`.
+fn param_text(value: &Value) -> String {
+  match value {
+    Value::String(s) => s.clone(),
+    Value::Number(n) => match n.as_f64() {
+      Some(f) if !n.is_i64() && !n.is_u64() && f.fract() == 0.0 && f.abs() < 1e15 => {
+        format!("{f:.0}")
+      }
+      _ => n.to_string(),
+    },
+    value => value.to_string(),
+  }
+}
+
+/// Translations of `AppError` messages, by language tag.
+///
+/// Messages missing from a language keep their `DEFAULT_LANGUAGE` text.
+#[derive(Debug, Clone)]
+pub struct MessageCatalog(Arc<CatalogInner>);
+
+#[derive(Debug, Clone)]
+struct CatalogInner {
+  fallback: String,
+  languages: BTreeMap<String, Messages>,
+}
+
+impl Default for MessageCatalog {
+  fn default() -> Self {
+    Self::new(DEFAULT_LANGUAGE)
+  }
+}
+
+impl MessageCatalog {
+  /// A catalog used when none of the `Accept-Language` tags are known.
+  pub fn new(fallback: impl Into<String>) -> Self {
+    Self(Arc::new(CatalogInner {
+      fallback: fallback.into().to_ascii_lowercase(),
+      languages: BTreeMap::new(),
+    }))
+  }
+
+  pub fn language(mut self, tag: impl Into<String>, messages: Messages) -> Self {
+    Arc::make_mut(&mut self.0)
+      .languages
+      .insert(tag.into().to_ascii_lowercase(), messages);
+    self
+  }
+
+  /// The first known language of `accept`, trying `fr` for `fr-ch`, or the fallback.
+  pub fn negotiate<'a>(&'a self, accept: &'a AcceptLanguage) -> &'a str {
+    let known = |tag: &str| tag == DEFAULT_LANGUAGE || self.0.languages.contains_key(tag);
+    accept
+      .0
+      .iter()
+      .find_map(|tag| {
+        let primary = tag.split('-').next().unwrap_or_default();
+        [tag.as_str(), primary].into_iter().find(|tag| known(tag))
+      })
+      .unwrap_or(&self.0.fallback)
+  }
+
+  /// Translate the `error` and validation messages of `output` into `language`, returning
+  /// whether any of them was translated.
+  pub fn localize(&self, language: &str, output: &mut AppErrorOutput) -> bool {
+    let Some(messages) = self.0.languages.get(language) else {
+      return false;
+    };
+    let mut translated = false;
+    if let Some(message) = messages.codes.get(&output.code) {
+      output.error = message.clone();
+      translated = true;
+    }
+    if output.code != AppErrorCode::Validation {
+      return translated;
+    }
+    // in debug mode, they are wrapped along with the debug info
+    let fields = match output.error_details.as_mut() {
+      Some(Value::Object(details)) => details.get_mut("details"),
+      details => details,
+    };
+    let fields = fields.and_then(Value::as_array_mut);
+    for field in fields
+      .into_iter()
+      .flatten()
+      .filter_map(Value::as_object_mut)
+    {
+      let Some(rule) = field.get("code").and_then(Value::as_str) else {
+        continue;
+      };
+      if let Some(message) = messages.rule_message(rule, field.get("params")) {
+        field.insert("message".to_owned(), message.into());
+        translated = true;
+      }
+    }
+    translated
+  }
+}
+
+/// Middleware that translates `AppError` responses into the `Accept-Language` of the request,
+/// and sets their `Content-Language` to it. Responses without any translated message are left
+/// as they are, in `DEFAULT_LANGUAGE`.
+///
+/// Problem details are translated too, on either side of `error_format`. Reasons given as
+/// `error_details` are not, so their `detail` stays as the handler wrote it.
+///
+/// ```ignore
+/// router.layer(axum::middleware::from_fn_with_state(catalog, localize_errors))
+/// ```
+pub async fn localize_errors(
+  State(catalog): State<MessageCatalog>,
+  accept: AcceptLanguage,
+  request: Request,
+  next: Next,
+) -> Response {
+  let language = catalog.negotiate(&accept).to_owned();
+  let response = next.run(request).await;
+
+  let (mut parts, body) = response.into_parts();
+  let Some(mut output) = parts.extensions.get::<AppErrorOutput>().cloned() else {
+    return Response::from_parts(parts, body);
+  };
+
+  if !catalog.localize(&language, &mut output) {
+    return Response::from_parts(parts, body);
+  }
+  if let Ok(language) = HeaderValue::from_str(&language) {
+    parts.headers.insert(header::CONTENT_LANGUAGE, language);
+  }
+  rerender(parts, body, output)
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn accept(value: &str) -> Vec<String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      header::ACCEPT_LANGUAGE,
+      HeaderValue::from_str(value).unwrap(),
+    );
+    AcceptLanguage::from_headers(&headers).0
+  }
+
+  fn tags(tags: &[&str]) -> AcceptLanguage {
+    AcceptLanguage(tags.iter().map(|tag| tag.to_string()).collect())
+  }
+
+  #[test]
+  fn sorts_accept_language_by_quality() {
+    assert_eq!(
+      accept("en;q=0.8, fr-CH, de;q=0.9, fr;q=0.9"),
+      ["fr-ch", "de", "fr", "en"]
+    );
+  }
+
+  #[test]
+  fn skips_wildcards_refused_and_malformed_ranges() {
+    assert_eq!(accept("*;q=0.5, de;q=0, it;q=high, , es"), ["es"]);
+  }
+
+  #[test]
+  fn negotiates_the_primary_language_then_the_fallback() {
+    let catalog = MessageCatalog::new("de").language("FR", Messages::default());
+    assert_eq!(catalog.negotiate(&tags(&["fr-ch", "en"])), "fr");
+    assert_eq!(catalog.negotiate(&tags(&["it", "en"])), "en");
+    assert_eq!(catalog.negotiate(&tags(&["it"])), "de");
+    assert_eq!(catalog.negotiate(&tags(&[])), "de");
+  }
+
+  #[test]
+  fn fills_rule_messages_in_with_their_params() {
+    let messages = Messages::default()
+      .rule("range", "doit être compris entre {min} et {max}")
+      .rule("must_match", "doit être égal à {other}")
+      .rule("open", "reste {ouvert");
+    let params = json!({ "min": 1.0, "max": 2.5, "other": "password" });
+    assert_eq!(
+      messages.rule_message("range", Some(&params)).as_deref(),
+      Some("doit être compris entre 1 et 2.5")
+    );
+    assert_eq!(
+      messages
+        .rule_message("must_match", Some(&params))
+        .as_deref(),
+      Some("doit être égal à password")
+    );
+    assert_eq!(
+      messages.rule_message("open", None).as_deref(),
+      Some("reste {ouvert")
+    );
+  }
+
+  #[test]
+  fn keeps_rule_messages_missing_a_param_or_a_translation() {
+    let messages = Messages::default().rule("length", "au plus {max} caractères");
+    let params = json!({ "min": 5 });
+    assert_eq!(messages.rule_message("length", Some(&params)), None);
+    assert_eq!(messages.rule_message("length", None), None);
+    assert_eq!(messages.rule_message("range", Some(&params)), None);
+  }
+
+  #[test]
+  fn reports_whether_anything_was_translated() {
+    let catalog = MessageCatalog::default().language(
+      "fr",
+      Messages::default().code(AppErrorCode::NotFound, "Introuvable"),
+    );
+    let mut output = AppErrorOutput::new(AppErrorCode::NotFound, "Not Found", None);
+    assert!(catalog.localize("fr", &mut output));
+    assert_eq!(output.error, "Introuvable");
+
+    let mut output = AppErrorOutput::new(AppErrorCode::Internal, "Internal Server Error", None);
+    assert!(!catalog.localize("fr", &mut output));
+    assert!(!catalog.localize("en", &mut output));
+    assert_eq!(output.error, "Internal Server Error");
+  }
+}
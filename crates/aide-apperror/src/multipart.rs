@@ -0,0 +1,851 @@
+use std::{fmt, mem, slice, vec};
+
+use axum::{
+  body::Bytes,
+  extract::{
+    multipart::{Field, Multipart},
+    FromRequest, Request,
+  },
+};
+use schemars::{
+  gen::SchemaGenerator,
+  schema::{InstanceType, Metadata, Schema, SchemaObject},
+  JsonSchema,
+};
+use serde::{
+  de::{self, value::SeqDeserializer, DeserializeOwned, IntoDeserializer, MapAccess, Visitor},
+  forward_to_deserialize_any, Deserialize, Deserializer, Serialize,
+};
+use validator::Validate;
+
+use crate::{
+  details::{serde_field_names, ErrorDetails},
+  error::{AppError, AppErrorCode},
+};
+
+/// The rules the files of a `MultipartValidate` form must follow, by field name.
+pub trait MultipartForm {
+  /// Files of fields without a rule are only limited by the body limit of the router.
+  const FILE_RULES: &'static [FileRule] = &[];
+}
+
+/// The size and media types accepted for the files of a field.
+#[derive(Debug, Clone, Copy)]
+pub struct FileRule {
+  pub field: &'static str,
+  /// The maximum size of each file, in bytes.
+  pub max_size: Option<usize>,
+  /// The accepted media types, e.g. `application/pdf` or `image/*`. Empty accepts any.
+  pub accept: &'static [&'static str],
+}
+
+impl FileRule {
+  pub const fn new(field: &'static str) -> Self {
+    Self {
+      field,
+      max_size: None,
+      accept: &[],
+    }
+  }
+
+  pub const fn max_size(mut self, max_size: usize) -> Self {
+    self.max_size = Some(max_size);
+    self
+  }
+
+  pub const fn accept(mut self, accept: &'static [&'static str]) -> Self {
+    self.accept = accept;
+    self
+  }
+
+  fn accepts(&self, content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    self.accept.is_empty()
+      || self
+        .accept
+        .iter()
+        .any(|accept| match accept.strip_suffix("/*") {
+          Some(kind) => essence
+            .split_once('/')
+            .is_some_and(|(k, _)| k.eq_ignore_ascii_case(kind)),
+          None => essence.eq_ignore_ascii_case(accept),
+        })
+  }
+}
+
+/// A file of a `multipart/form-data` request, documented as a binary string.
+#[derive(Clone)]
+pub struct UploadedFile {
+  pub file_name: Option<String>,
+  /// The media type declared by the client, `application/octet-stream` if it did not.
+  pub content_type: String,
+  pub bytes: Bytes,
+}
+
+impl fmt::Debug for UploadedFile {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("UploadedFile")
+      .field("file_name", &self.file_name)
+      .field("content_type", &self.content_type)
+      .field("len", &self.bytes.len())
+      .finish()
+  }
+}
+
+impl JsonSchema for UploadedFile {
+  fn schema_name() -> String {
+    "UploadedFile".to_owned()
+  }
+
+  fn is_referenceable() -> bool {
+    false
+  }
+
+  fn json_schema(_: &mut SchemaGenerator) -> Schema {
+    SchemaObject {
+      metadata: Some(Box::new(Metadata {
+        description: Some("A file.".to_owned()),
+        ..Default::default()
+      })),
+      instance_type: Some(InstanceType::String.into()),
+      format: Some("binary".to_owned()),
+      ..Default::default()
+    }
+    .into()
+  }
+}
+
+/// A file exceeding the `max_size` of its field.
+#[derive(Debug, Clone, thiserror::Error, Serialize, JsonSchema)]
+#[error("a file of `{field}` exceeds {max_size} bytes")]
+pub struct FileTooLarge {
+  /// The form field of the file.
+  pub field: String,
+  /// The maximum size of a file of this field, in bytes.
+  pub max_size: usize,
+}
+
+/// A file of a media type its field does not accept.
+#[derive(Debug, Clone, thiserror::Error, Serialize, JsonSchema)]
+#[error("a file of `{field}` is `{content_type}`, which is not accepted")]
+pub struct UnsupportedFileType {
+  /// The form field of the file.
+  pub field: String,
+  /// The media type of the file.
+  pub content_type: String,
+  /// The media types accepted for this field.
+  pub accept: Vec<String>,
+}
+
+impl ErrorDetails for FileTooLarge {
+  type Details = Self;
+
+  fn details(&self) -> Self::Details {
+    self.clone()
+  }
+}
+
+impl ErrorDetails for UnsupportedFileType {
+  type Details = Self;
+
+  fn details(&self) -> Self::Details {
+    self.clone()
+  }
+}
+
+/// Extract `T` from a `multipart/form-data` request, and apply validation checks.
+pub(crate) async fn extract_form<T, S>(req: Request, state: &S) -> Result<T, AppError>
+where
+  T: MultipartForm + DeserializeOwned + Validate + JsonSchema,
+  S: Send + Sync,
+{
+  let multipart = Multipart::from_request(req, state).await?;
+  let form: T = read_form(multipart).await?;
+  form.validate().map_err(serde_field_names::<T>)?;
+  Ok(form)
+}
+
+/// The error codes of `extract_form`, `FileTooLarge` and `UnsupportedFileType` only when `T`
+/// has rules for them.
+pub(crate) fn form_error_codes<T: MultipartForm>() -> Vec<AppErrorCode> {
+  let mut codes = vec![
+    AppErrorCode::Validation,
+    AppErrorCode::MultipartDataError,
+    AppErrorCode::MultipartRejection,
+    AppErrorCode::MultipartError,
+    AppErrorCode::MultipartPayloadTooLarge,
+  ];
+  if T::FILE_RULES.iter().any(|rule| rule.max_size.is_some()) {
+    codes.push(AppErrorCode::FileTooLarge);
+  }
+  if T::FILE_RULES.iter().any(|rule| !rule.accept.is_empty()) {
+    codes.push(AppErrorCode::UnsupportedFileType);
+  }
+  codes
+}
+
+/// Document the `multipart/form-data` body of `T`, with the accepted media types of its files.
+pub(crate) fn set_form_body<T: MultipartForm + JsonSchema>(
+  ctx: &mut aide::gen::GenContext,
+  operation: &mut aide::openapi::Operation,
+) {
+  let schema = ctx.schema.subschema_for::<T>();
+  let encoding = T::FILE_RULES
+    .iter()
+    .filter(|rule| !rule.accept.is_empty())
+    .map(|rule| {
+      let encoding = aide::openapi::Encoding {
+        content_type: Some(rule.accept.join(", ")),
+        ..Default::default()
+      };
+      (rule.field.to_owned(), encoding)
+    })
+    .collect();
+
+  aide::operation::set_body(
+    ctx,
+    operation,
+    aide::openapi::RequestBody {
+      description: None,
+      content: indexmap::IndexMap::from_iter([(
+        "multipart/form-data".to_owned(),
+        aide::openapi::MediaType {
+          schema: Some(aide::openapi::SchemaObject {
+            json_schema: schema,
+            external_docs: None,
+            example: None,
+          }),
+          encoding,
+          ..Default::default()
+        },
+      )]),
+      required: true,
+      extensions: Default::default(),
+    },
+  );
+}
+
+/// Read every field of `multipart`, enforcing the `FILE_RULES` of `T`, and deserialize them.
+///
+/// Text fields are deserialized like a urlencoded form, parts with a file name as
+/// `UploadedFile`. Repeated fields deserialize into sequences.
+async fn read_form<T>(mut multipart: Multipart) -> Result<T, AppError>
+where
+  T: MultipartForm + DeserializeOwned,
+{
+  let mut fields: Vec<(String, Vec<Part>)> = Vec::new();
+  while let Some(field) = multipart.next_field().await? {
+    let name = field.name().unwrap_or_default().to_owned();
+    let part = if field.file_name().is_some() {
+      let rule = T::FILE_RULES.iter().find(|rule| rule.field == name);
+      Part::File(read_file(field, &name, rule).await?)
+    } else {
+      Part::Text(field.text().await?)
+    };
+    match fields.iter_mut().find(|(field, _)| *field == name) {
+      Some((_, parts)) => parts.push(part),
+      None => fields.push((name, vec![part])),
+    }
+  }
+
+  T::deserialize(FormDeserializer(fields)).map_err(AppError::MultipartDataError)
+}
+
+async fn read_file(
+  mut field: Field<'_>,
+  name: &str,
+  rule: Option<&FileRule>,
+) -> Result<UploadedFile, AppError> {
+  let file_name = field.file_name().map(str::to_owned);
+  let content_type = field
+    .content_type()
+    .unwrap_or("application/octet-stream")
+    .to_owned();
+  if let Some(rule) = rule.filter(|rule| !rule.accepts(&content_type)) {
+    return Err(
+      UnsupportedFileType {
+        field: name.to_owned(),
+        content_type,
+        accept: rule.accept.iter().map(|a| a.to_string()).collect(),
+      }
+      .into(),
+    );
+  }
+
+  // checked while reading, so a large file is rejected before it is buffered
+  let max_size = rule.and_then(|rule| rule.max_size);
+  let mut bytes = Vec::new();
+  while let Some(chunk) = field.chunk().await? {
+    if let Some(max_size) = max_size.filter(|max| bytes.len() + chunk.len() > *max) {
+      return Err(
+        FileTooLarge {
+          field: name.to_owned(),
+          max_size,
+        }
+        .into(),
+      );
+    }
+    bytes.extend_from_slice(&chunk);
+  }
+
+  Ok(UploadedFile {
+    file_name,
+    content_type,
+    bytes: bytes.into(),
+  })
+}
+
+/// The fields `UploadedFile` deserializes from, `file_name` is left out when there is none.
+const FILE_FIELDS: &[&str] = &["file_name", "content_type", "bytes"];
+
+impl<'de> Deserialize<'de> for UploadedFile {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct FileVisitor;
+
+    impl<'de> Visitor<'de> for FileVisitor {
+      type Value = UploadedFile;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a file of a multipart/form-data request")
+      }
+
+      fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut file_name, mut content_type, mut bytes) = (None, None, None);
+        while let Some(key) = map.next_key::<String>()? {
+          match key.as_str() {
+            "file_name" => file_name = Some(map.next_value()?),
+            "content_type" => content_type = Some(map.next_value()?),
+            "bytes" => bytes = Some(map.next_value::<ByteBuf>()?.0),
+            _ => {
+              map.next_value::<de::IgnoredAny>()?;
+            }
+          }
+        }
+        Ok(UploadedFile {
+          file_name,
+          content_type: content_type.ok_or_else(|| de::Error::missing_field("content_type"))?,
+          bytes: bytes.ok_or_else(|| de::Error::missing_field("bytes"))?,
+        })
+      }
+    }
+
+    deserializer.deserialize_struct("UploadedFile", FILE_FIELDS, FileVisitor)
+  }
+}
+
+/// The content of a file, handed over without a copy.
+struct ByteBuf(Bytes);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    struct BytesVisitor;
+
+    impl Visitor<'_> for BytesVisitor {
+      type Value = ByteBuf;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+      }
+
+      fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Ok(ByteBuf(Bytes::copy_from_slice(bytes)))
+      }
+
+      fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(ByteBuf(bytes.into()))
+      }
+    }
+
+    deserializer.deserialize_byte_buf(BytesVisitor)
+  }
+}
+
+enum Part {
+  Text(String),
+  File(UploadedFile),
+}
+
+type Error = de::value::Error;
+
+/// Deserializes the fields of a form into a struct or a map.
+pub(crate) struct FormDeserializer(Vec<(String, Vec<Part>)>);
+
+impl FormDeserializer {
+  /// A form of text fields only, e.g. headers.
+  pub(crate) fn text(fields: Vec<(String, Vec<String>)>) -> Self {
+    let fields = fields
+      .into_iter()
+      .map(|(name, values)| (name, values.into_iter().map(Part::Text).collect()))
+      .collect();
+    Self(fields)
+  }
+}
+
+impl<'de> Deserializer<'de> for FormDeserializer {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_map(FormAccess {
+      fields: self.0.into_iter(),
+      parts: None,
+    })
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+    unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+struct FormAccess {
+  fields: vec::IntoIter<(String, Vec<Part>)>,
+  parts: Option<Vec<Part>>,
+}
+
+impl<'de> MapAccess<'de> for FormAccess {
+  type Error = Error;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+  where
+    K: de::DeserializeSeed<'de>,
+  {
+    let Some((name, parts)) = self.fields.next() else {
+      return Ok(None);
+    };
+    self.parts = Some(parts);
+    seed.deserialize(name.into_deserializer()).map(Some)
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+  where
+    V: de::DeserializeSeed<'de>,
+  {
+    let parts = self.parts.take().unwrap_or_default();
+    seed.deserialize(PartsDeserializer(parts))
+  }
+}
+
+/// All the parts of a field, a sequence or a single value.
+struct PartsDeserializer(Vec<Part>);
+
+impl PartsDeserializer {
+  fn single(self) -> Result<PartDeserializer, Error> {
+    let count = self.0.len();
+    let mut parts = self.0.into_iter();
+    match (parts.next(), count) {
+      (Some(part), 1) => Ok(PartDeserializer(part)),
+      _ => Err(de::Error::custom(format!(
+        "expected a single value, found {count}"
+      ))),
+    }
+  }
+}
+
+/// Deserialize through the single part of the field.
+macro_rules! single_part {
+  ($($method:ident),* $(,)?) => {
+    $(
+      fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.single()?.$method(visitor)
+      }
+    )*
+  };
+}
+
+impl<'de> Deserializer<'de> for PartsDeserializer {
+  type Error = Error;
+
+  fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_seq(SeqDeserializer::new(self.0.into_iter()))
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    self.single()?.deserialize_newtype_struct(name, visitor)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    name: &'static str,
+    variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    self.single()?.deserialize_enum(name, variants, visitor)
+  }
+
+  single_part!(
+    deserialize_any,
+    deserialize_bool,
+    deserialize_i8,
+    deserialize_i16,
+    deserialize_i32,
+    deserialize_i64,
+    deserialize_u8,
+    deserialize_u16,
+    deserialize_u32,
+    deserialize_u64,
+    deserialize_f32,
+    deserialize_f64,
+    deserialize_str,
+    deserialize_string,
+  );
+
+  forward_to_deserialize_any! {
+    i128 u128 char bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+    ignored_any
+  }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Part {
+  type Deserializer = PartDeserializer;
+
+  fn into_deserializer(self) -> Self::Deserializer {
+    PartDeserializer(self)
+  }
+}
+
+struct PartDeserializer(Part);
+
+/// The fields of a file, see `FILE_FIELDS`.
+struct FileAccess {
+  file: UploadedFile,
+  fields: slice::Iter<'static, &'static str>,
+  field: &'static str,
+}
+
+impl FileAccess {
+  fn new(file: UploadedFile) -> Self {
+    Self {
+      file,
+      fields: FILE_FIELDS.iter(),
+      field: "",
+    }
+  }
+}
+
+impl<'de> MapAccess<'de> for FileAccess {
+  type Error = Error;
+
+  fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+  where
+    K: de::DeserializeSeed<'de>,
+  {
+    let has_file_name = self.file.file_name.is_some();
+    let Some(field) = self
+      .fields
+      .find(|field| has_file_name || **field != "file_name")
+    else {
+      return Ok(None);
+    };
+    self.field = field;
+    seed.deserialize(field.into_deserializer()).map(Some)
+  }
+
+  fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+  where
+    V: de::DeserializeSeed<'de>,
+  {
+    let file = &mut self.file;
+    match self.field {
+      "file_name" => seed.deserialize(
+        file
+          .file_name
+          .take()
+          .unwrap_or_default()
+          .into_deserializer(),
+      ),
+      "content_type" => seed.deserialize(mem::take(&mut file.content_type).into_deserializer()),
+      _ => seed.deserialize(ByteBufDeserializer(mem::take(&mut file.bytes).into())),
+    }
+  }
+}
+
+/// Hands the content of a file over as an owned buffer.
+struct ByteBufDeserializer(Vec<u8>);
+
+impl<'de> Deserializer<'de> for ByteBufDeserializer {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_byte_buf(self.0)
+  }
+
+  forward_to_deserialize_any! {
+    bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+    unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+  }
+}
+
+impl PartDeserializer {
+  fn text(self) -> Result<String, Error> {
+    match self.0 {
+      Part::Text(text) => Ok(text),
+      Part::File(_) => Err(de::Error::custom("expected a text field, found a file")),
+    }
+  }
+}
+
+/// Parse the text of the part, like a urlencoded form value.
+macro_rules! parse_text {
+  ($($method:ident => $visit:ident),* $(,)?) => {
+    $(
+      fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.$visit(self.text()?.parse().map_err(de::Error::custom)?)
+      }
+    )*
+  };
+}
+
+impl<'de> Deserializer<'de> for PartDeserializer {
+  type Error = Error;
+
+  fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    match self.0 {
+      Part::Text(text) => visitor.visit_string(text),
+      Part::File(file) => visitor.visit_map(FileAccess::new(file)),
+    }
+  }
+
+  fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+    visitor.visit_some(self)
+  }
+
+  fn deserialize_newtype_struct<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_newtype_struct(self)
+  }
+
+  fn deserialize_enum<V: Visitor<'de>>(
+    self,
+    _name: &'static str,
+    _variants: &'static [&'static str],
+    visitor: V,
+  ) -> Result<V::Value, Error> {
+    visitor.visit_enum(self.text()?.into_deserializer())
+  }
+
+  parse_text!(
+    deserialize_bool => visit_bool,
+    deserialize_i8 => visit_i8,
+    deserialize_i16 => visit_i16,
+    deserialize_i32 => visit_i32,
+    deserialize_i64 => visit_i64,
+    deserialize_u8 => visit_u8,
+    deserialize_u16 => visit_u16,
+    deserialize_u32 => visit_u32,
+    deserialize_u64 => visit_u64,
+    deserialize_f32 => visit_f32,
+    deserialize_f64 => visit_f64,
+  );
+
+  forward_to_deserialize_any! {
+    i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+    identifier ignored_any
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::{body::Body, http::header};
+
+  use super::*;
+
+  #[derive(Debug, Deserialize, JsonSchema, Validate)]
+  struct Upload {
+    note: Option<String>,
+    file: UploadedFile,
+  }
+
+  impl MultipartForm for Upload {
+    const FILE_RULES: &'static [FileRule] = &[FileRule::new("file")
+      .max_size(4)
+      .accept(&["image/*", "application/pdf"])];
+  }
+
+  #[derive(Debug, Deserialize, JsonSchema, Validate)]
+  struct Repeated {
+    tags: Vec<String>,
+    files: Vec<UploadedFile>,
+    count: u32,
+  }
+
+  impl MultipartForm for Repeated {}
+
+  #[derive(Debug, Deserialize, JsonSchema, Validate)]
+  struct Flattened {
+    title: String,
+    #[serde(flatten)]
+    attachment: Attachment,
+  }
+
+  #[derive(Debug, Deserialize, JsonSchema)]
+  struct Attachment {
+    note: String,
+    file: UploadedFile,
+  }
+
+  impl MultipartForm for Flattened {}
+
+  enum TestPart<'a> {
+    Text(&'a str, &'a str),
+    /// The field, file name, content type and content of a file.
+    File(&'a str, &'a str, &'a str, &'a [u8]),
+  }
+
+  const BOUNDARY: &str = "test-boundary";
+
+  async fn extract<T>(parts: &[TestPart<'_>]) -> Result<T, AppError>
+  where
+    T: MultipartForm + DeserializeOwned + Validate + JsonSchema,
+  {
+    let mut body = Vec::new();
+    for part in parts {
+      body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+      match part {
+        TestPart::Text(name, value) => body.extend_from_slice(
+          format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n").as_bytes(),
+        ),
+        TestPart::File(name, file_name, content_type, content) => {
+          body.extend_from_slice(
+            format!(
+              "Content-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\
+               Content-Type: {content_type}\r\n\r\n"
+            )
+            .as_bytes(),
+          );
+          body.extend_from_slice(content);
+          body.extend_from_slice(b"\r\n");
+        }
+      }
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    let request = Request::builder()
+      .header(
+        header::CONTENT_TYPE,
+        format!("multipart/form-data; boundary={BOUNDARY}"),
+      )
+      .body(Body::from(body))
+      .unwrap();
+    extract_form(request, &()).await
+  }
+
+  #[tokio::test]
+  async fn accepts_files_following_their_rules() {
+    let upload: Upload = extract(&[
+      TestPart::Text("note", "a cat"),
+      TestPart::File("file", "cat.png", "image/png", b"cat"),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(upload.note.as_deref(), Some("a cat"));
+    assert_eq!(upload.file.file_name.as_deref(), Some("cat.png"));
+    assert_eq!(upload.file.content_type, "image/png");
+    assert_eq!(upload.file.bytes, "cat");
+
+    let upload: Upload = extract(&[TestPart::File(
+      "file",
+      "cat.pdf",
+      "application/pdf",
+      b"cat!",
+    )])
+    .await
+    .unwrap();
+    assert_eq!(upload.note, None);
+  }
+
+  #[tokio::test]
+  async fn rejects_files_over_their_max_size() {
+    let error = extract::<Upload>(&[TestPart::File("file", "cat.png", "image/png", b"cats!")])
+      .await
+      .unwrap_err();
+    let AppError::FileTooLarge(error) = error else {
+      panic!("expected FileTooLarge, got {error:?}");
+    };
+    assert_eq!(error.field, "file");
+    assert_eq!(error.max_size, 4);
+  }
+
+  #[tokio::test]
+  async fn rejects_files_of_other_media_types() {
+    let error = extract::<Upload>(&[TestPart::File("file", "cat.txt", "text/plain", b"cat")])
+      .await
+      .unwrap_err();
+    let AppError::UnsupportedFileType(error) = error else {
+      panic!("expected UnsupportedFileType, got {error:?}");
+    };
+    assert_eq!(error.content_type, "text/plain");
+    assert_eq!(error.accept, ["image/*", "application/pdf"]);
+  }
+
+  #[tokio::test]
+  async fn deserializes_repeated_fields_into_sequences() {
+    let repeated: Repeated = extract(&[
+      TestPart::Text("tags", "a"),
+      TestPart::File("files", "a.txt", "text/plain", b"a"),
+      TestPart::Text("count", "2"),
+      TestPart::Text("tags", "b"),
+      TestPart::File("files", "b.txt", "text/plain", b"b"),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(repeated.tags, ["a", "b"]);
+    assert_eq!(repeated.count, 2);
+    let files: Vec<_> = repeated.files.iter().map(|f| &f.bytes).collect();
+    assert_eq!(files, ["a", "b"]);
+  }
+
+  #[tokio::test]
+  async fn rejects_repeated_single_fields() {
+    let error = extract::<Upload>(&[
+      TestPart::Text("note", "a"),
+      TestPart::Text("note", "b"),
+      TestPart::File("file", "cat.png", "image/png", b"cat"),
+    ])
+    .await
+    .unwrap_err();
+    assert!(
+      matches!(error, AppError::MultipartDataError(_)),
+      "{error:?}"
+    );
+  }
+
+  #[tokio::test]
+  async fn rejects_text_for_files() {
+    let error = extract::<Upload>(&[TestPart::Text("file", "cat")])
+      .await
+      .unwrap_err();
+    assert!(
+      matches!(error, AppError::MultipartDataError(_)),
+      "{error:?}"
+    );
+  }
+
+  #[tokio::test]
+  async fn deserializes_flattened_fields() {
+    let flattened: Flattened = extract(&[
+      TestPart::Text("title", "pets"),
+      TestPart::Text("note", "a cat"),
+      TestPart::File("file", "cat.png", "image/png", b"cat"),
+    ])
+    .await
+    .unwrap();
+    assert_eq!(flattened.title, "pets");
+    assert_eq!(flattened.attachment.note, "a cat");
+    assert_eq!(
+      flattened.attachment.file.file_name.as_deref(),
+      Some("cat.png")
+    );
+    assert_eq!(flattened.attachment.file.bytes, "cat");
+  }
+}
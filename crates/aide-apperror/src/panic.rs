@@ -0,0 +1,46 @@
+use std::any::Any;
+
+use aide::transform::TransformOpenApi;
+use axum::response::{IntoResponse, Response};
+use tower_http::catch_panic::CatchPanicLayer;
+
+use crate::{
+  error::{AppError, AppErrorCode},
+  extractors::{for_each_operation, set_inferred_response},
+};
+
+type PanicHandler = fn(Box<dyn Any + Send + 'static>) -> Response;
+
+/// Layer that turns a panic in a handler into an `AppError::Internal` response, instead of
+/// dropping the connection. The panic message is only logged, along with the `error_id`.
+///
+/// Add it before `record_errors` and `error_format`, so they see the response too.
+///
+/// ```ignore
+/// router.layer(catch_panic())
+/// ```
+pub fn catch_panic() -> CatchPanicLayer<PanicHandler> {
+  CatchPanicLayer::custom(panic_response as PanicHandler)
+}
+
+fn panic_response(payload: Box<dyn Any + Send + 'static>) -> Response {
+  let message = if let Some(message) = payload.downcast_ref::<&str>() {
+    message
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message.as_str()
+  } else {
+    "unknown panic payload"
+  };
+  AppError::internal(format!("handler panicked: {message}")).into_response()
+}
+
+/// Document the `AppError::Internal` 500 response on every operation, as any of them can panic.
+///
+/// ```ignore
+/// router.finish_api_with(&mut api, |api| api.with(document_panics))
+/// ```
+pub fn document_panics(api: TransformOpenApi) -> TransformOpenApi {
+  for_each_operation(api, |ctx, operation| {
+    set_inferred_response(ctx, operation, AppErrorCode::Internal)
+  })
+}
@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use aide::transform::TransformOpenApi;
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use indexmap::IndexMap;
+
+use crate::{
+  error::{AppErrorCode, AppErrorOutput},
+  extractors::{for_each_operation, set_inferred_response, unset_inferred_response},
+  problem::ErrorFormat,
+};
+
+/// Remaps error codes before they are sent, e.g. `AuthKeyNoAccess` to `NotFound`, to not reveal
+/// that a resource exists to callers that can not access it.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorPolicy(Arc<IndexMap<AppErrorCode, AppErrorCode>>);
+
+/// The code of an `AppError` response before the `ErrorPolicy` remapped it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RemappedFrom(pub AppErrorCode);
+
+impl ErrorPolicy {
+  /// Send `to` wherever `from` would be sent.
+  ///
+  /// Chains are followed, remapping `A` to `B` and `B` to `C` sends `C` for both.
+  ///
+  /// # Panics
+  ///
+  /// If it would remap `from` back to itself.
+  pub fn remap(mut self, from: AppErrorCode, to: AppErrorCode) -> Self {
+    let to = self.resolve(to);
+    assert!(
+      from != to,
+      "the error policy would remap {} to itself",
+      from.as_str()
+    );
+    let remaps = Arc::make_mut(&mut self.0);
+    for target in remaps.values_mut() {
+      if *target == from {
+        *target = to;
+      }
+    }
+    remaps.insert(from, to);
+    self
+  }
+
+  /// The code sent in place of `code`.
+  pub fn resolve(&self, code: AppErrorCode) -> AppErrorCode {
+    self.0.get(&code).copied().unwrap_or(code)
+  }
+
+  /// `output` as it is sent, with the message of the remapped code and its `error_id` kept.
+  pub fn apply(&self, output: AppErrorOutput) -> AppErrorOutput {
+    let code = self.resolve(output.code);
+    if code == output.code {
+      return output;
+    }
+    AppErrorOutput {
+      // the details of the original code could still reveal it
+      error_details: None,
+      error_id: output.error_id,
+      ..code.create_example()
+    }
+  }
+
+  /// Document the remapped codes in place of the original ones, on every operation.
+  ///
+  /// ```ignore
+  /// router.finish_api_with(&mut api, |api| api.with(|api| policy.document(api)))
+  /// ```
+  pub fn document<'a>(&self, api: TransformOpenApi<'a>) -> TransformOpenApi<'a> {
+    for_each_operation(api, |ctx, operation| {
+      for (from, to) in self.0.iter() {
+        if unset_inferred_response(operation, *from) {
+          set_inferred_response(ctx, operation, *to);
+        }
+      }
+    })
+  }
+}
+
+/// Middleware that applies the `ErrorPolicy` to `AppError` responses.
+///
+/// Add it before `record_errors`, so the journal records what was sent along with the original code,
+/// and before `localize_errors`, so the remapped code is translated. The response keeps its
+/// `ErrorFormat`.
+///
+/// ```ignore
+/// router.layer(axum::middleware::from_fn_with_state(policy, apply_error_policy))
+/// ```
+pub async fn apply_error_policy(
+  State(policy): State<ErrorPolicy>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let response = next.run(request).await;
+  let Some(output) = response.extensions().get::<AppErrorOutput>() else {
+    return response;
+  };
+  if policy.resolve(output.code) == output.code {
+    return response;
+  }
+
+  let original = RemappedFrom(output.code);
+  let output = policy.apply(output.clone());
+  let (parts, _) = response.into_parts();
+  let format = ErrorFormat::of(&parts.headers);
+  let mut remapped = output.clone().into_response();
+  remapped.extensions_mut().insert(original);
+  // keeps e.g. the `ErrorSourceChain`, but the remapped `AppErrorOutput`
+  let extensions = std::mem::take(remapped.extensions_mut());
+  remapped.extensions_mut().extend(parts.extensions);
+  remapped.extensions_mut().extend(extensions);
+  match format {
+    Some(ErrorFormat::ProblemJson) => {
+      let (parts, _) = remapped.into_parts();
+      ErrorFormat::ProblemJson.render(parts, output)
+    }
+    _ => remapped,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use aide::{
+    axum::{routing::get, ApiRouter},
+    openapi::{OpenApi, ReferenceOr, StatusCode},
+  };
+  use axum::{body::Body, middleware::from_fn_with_state, Router};
+  use tower::ServiceExt;
+
+  use super::*;
+  use crate::{
+    error::{
+      codes::{AuthKeyNoAccess, NotFound},
+      AppError,
+    },
+    error_set::ApiErrors,
+  };
+
+  async fn get_todo() -> Result<(), ApiErrors<(NotFound, AuthKeyNoAccess)>> {
+    Err(AppError::AuthKeyNoAccess.into())
+  }
+
+  fn app(policy: &ErrorPolicy) -> (Router, OpenApi) {
+    let mut api = OpenApi::default();
+    let router = ApiRouter::new()
+      .api_route("/todo", get(get_todo))
+      .finish_api_with(&mut api, |api| policy.document(api))
+      .layer(from_fn_with_state(policy.clone(), apply_error_policy));
+    (router, api)
+  }
+
+  /// The documented statuses of `GET /todo`.
+  fn documented(api: &OpenApi) -> Vec<u16> {
+    let Some(ReferenceOr::Item(path)) = api.paths.as_ref().unwrap().paths.get("/todo") else {
+      panic!("GET /todo is not documented");
+    };
+    let responses = path.get.as_ref().unwrap().responses.as_ref().unwrap();
+    let mut statuses: Vec<_> = responses
+      .responses
+      .keys()
+      .map(|status| match status {
+        StatusCode::Code(code) => *code,
+        StatusCode::Range(range) => *range,
+      })
+      .collect();
+    statuses.sort();
+    statuses
+  }
+
+  async fn sent(router: Router) -> (u16, AppErrorCode, Option<AppErrorCode>) {
+    let request = Request::get("/todo").body(Body::empty()).unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let output = response.extensions().get::<AppErrorOutput>().unwrap();
+    let original = response.extensions().get::<RemappedFrom>();
+    (
+      response.status().as_u16(),
+      output.code,
+      original.map(|original| original.0),
+    )
+  }
+
+  #[tokio::test]
+  async fn sends_and_documents_codes_as_they_are_without_remaps() {
+    let (router, api) = app(&ErrorPolicy::default());
+    assert_eq!(
+      sent(router).await,
+      (403, AppErrorCode::AuthKeyNoAccess, None)
+    );
+    assert_eq!(documented(&api), [200, 403, 404, 500]);
+  }
+
+  #[tokio::test]
+  async fn sends_and_documents_the_remapped_code() {
+    let policy =
+      ErrorPolicy::default().remap(AppErrorCode::AuthKeyNoAccess, AppErrorCode::NotFound);
+    let (router, api) = app(&policy);
+    assert_eq!(
+      sent(router).await,
+      (
+        404,
+        AppErrorCode::NotFound,
+        Some(AppErrorCode::AuthKeyNoAccess)
+      )
+    );
+    assert_eq!(documented(&api), [200, 404, 500]);
+  }
+
+  #[test]
+  fn follows_chains_of_remaps() {
+    let policy = ErrorPolicy::default()
+      .remap(AppErrorCode::AuthKeyMissing, AppErrorCode::AuthKeyInvalid)
+      .remap(AppErrorCode::AuthKeyInvalid, AppErrorCode::NotFound);
+    assert_eq!(
+      policy.resolve(AppErrorCode::AuthKeyMissing),
+      AppErrorCode::NotFound
+    );
+    assert_eq!(
+      policy.resolve(AppErrorCode::AuthKeyInvalid),
+      AppErrorCode::NotFound
+    );
+    assert_eq!(
+      policy.resolve(AppErrorCode::NotFound),
+      AppErrorCode::NotFound
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "would remap AUTH_KEY_INVALID to itself")]
+  fn rejects_remaps_back_to_the_same_code() {
+    ErrorPolicy::default()
+      .remap(AppErrorCode::AuthKeyMissing, AppErrorCode::AuthKeyInvalid)
+      .remap(AppErrorCode::AuthKeyInvalid, AppErrorCode::AuthKeyMissing);
+  }
+
+  #[test]
+  fn drops_the_details_of_the_original_code() {
+    let output = AppErrorOutput::new(AppErrorCode::Conflict, "Conflict", Some("taken".into()));
+    let output = ErrorPolicy::default()
+      .remap(AppErrorCode::Conflict, AppErrorCode::NotFound)
+      .apply(output);
+    assert_eq!(output.code, AppErrorCode::NotFound);
+    assert_eq!(output.error, "Not Found");
+    assert!(output.error_details.is_none());
+  }
+}
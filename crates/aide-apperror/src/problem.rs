@@ -0,0 +1,285 @@
+use axum::{
+  body::Body,
+  extract::{Request, State},
+  http::{header, response::Parts, HeaderMap, HeaderValue},
+  middleware::Next,
+  response::Response,
+};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use strum::IntoEnumIterator;
+use uuid::Uuid;
+
+use crate::error::{AppErrorCode, AppErrorOutput};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 9457 problem details, the `application/problem+json` representation of an `AppError`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[schemars(example = "problem_details_examples")]
+pub struct ProblemDetails {
+  /// A URI reference that identifies the problem type.
+  #[serde(rename = "type")]
+  pub type_: String,
+  /// A short, human-readable summary of the problem type.
+  pub title: String,
+  /// The HTTP status code.
+  pub status: u16,
+  /// A human-readable explanation of this occurrence of the problem. Only validation messages
+  /// are translated by `localize_errors`, reasons are sent as written.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub detail: Option<String>,
+  /// A URI reference that identifies this occurrence of the problem.
+  pub instance: String,
+  /// A stable, machine-readable error code.
+  pub code: AppErrorCode,
+  /// A unique error ID.
+  pub error_id: Uuid,
+  /// Optional Additional error details.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error_details: Option<Value>,
+}
+
+fn problem_details_examples() -> Vec<ProblemDetails> {
+  AppErrorCode::iter()
+    .map(|code| code.create_example().into())
+    .collect()
+}
+
+impl From<AppErrorOutput> for ProblemDetails {
+  fn from(output: AppErrorOutput) -> Self {
+    let code = output.code;
+    Self {
+      type_: format!("urn:problem-type:{}", code.as_str()),
+      title: output.error,
+      status: code.status_code_as_u16(),
+      detail: occurrence_detail(output.error_details.as_ref()),
+      instance: format!("urn:uuid:{}", output.error_id),
+      code,
+      error_id: output.error_id,
+      error_details: output.error_details,
+    }
+  }
+}
+
+/// The explanation of one occurrence, from its `error_details`: the reason given as a string,
+/// or the messages of the fields that failed validation. `None` for other details.
+fn occurrence_detail(details: Option<&Value>) -> Option<String> {
+  // in debug mode, they are wrapped along with the debug info
+  let details = match details? {
+    Value::Object(wrapped) if wrapped.contains_key("debug") => wrapped.get("details")?,
+    details => details,
+  };
+  match details {
+    Value::String(reason) => Some(reason.clone()),
+    Value::Array(fields) => {
+      let messages: Vec<String> = fields
+        .iter()
+        .filter_map(|field| {
+          let message = field.get("message")?.as_str()?;
+          match field.get("pointer").and_then(Value::as_str) {
+            Some(pointer) if !pointer.is_empty() => Some(format!("{pointer}: {message}")),
+            _ => Some(message.to_owned()),
+          }
+        })
+        .collect();
+      (!messages.is_empty()).then(|| messages.join("; "))
+    }
+    _ => None,
+  }
+}
+
+/// The representation used for `AppError` responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+  /// `application/json`, the `AppErrorOutput` shape.
+  #[default]
+  Json,
+  /// `application/problem+json`, the `ProblemDetails` shape.
+  ProblemJson,
+}
+
+impl ErrorFormat {
+  fn media_type(self) -> &'static str {
+    match self {
+      Self::Json => "application/json",
+      Self::ProblemJson => PROBLEM_JSON,
+    }
+  }
+
+  /// The format an error response was rendered in, `None` if its body is something else.
+  pub(crate) fn of(headers: &HeaderMap) -> Option<Self> {
+    let media = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    match media.split(';').next().unwrap_or_default().trim() {
+      PROBLEM_JSON => Some(Self::ProblemJson),
+      "application/json" => Some(Self::Json),
+      _ => None,
+    }
+  }
+
+  /// Renders `output` as the body of the response, and keeps it as its `AppErrorOutput`.
+  pub(crate) fn render(self, mut parts: Parts, output: AppErrorOutput) -> Response {
+    let body = match self {
+      Self::Json => serde_json::to_vec(&output),
+      Self::ProblemJson => serde_json::to_vec(&ProblemDetails::from(output.clone())),
+    };
+    let body = unsafe { body.unwrap_unchecked() };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+      header::CONTENT_TYPE,
+      HeaderValue::from_static(self.media_type()),
+    );
+    parts.extensions.insert(output);
+    Response::from_parts(parts, Body::from(body))
+  }
+
+  /// Picks the first of the two media types listed in `Accept`, or `self` if neither is.
+  fn negotiate(self, headers: &HeaderMap) -> Self {
+    headers
+      .get_all(header::ACCEPT)
+      .iter()
+      .filter_map(|v| v.to_str().ok())
+      .flat_map(|v| v.split(','))
+      .map(|v| v.split(';').next().unwrap_or_default().trim())
+      .find_map(|media| match media {
+        PROBLEM_JSON => Some(Self::ProblemJson),
+        "application/json" => Some(Self::Json),
+        _ => None,
+      })
+      .unwrap_or(self)
+  }
+}
+
+/// Middleware that renders `AppError` responses in the configured `ErrorFormat`, unless the
+/// request asks for the other one through `Accept`.
+///
+/// ```ignore
+/// router.layer(axum::middleware::from_fn_with_state(ErrorFormat::Json, error_format))
+/// ```
+pub async fn error_format(
+  State(format): State<ErrorFormat>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let format = format.negotiate(request.headers());
+  let response = next.run(request).await;
+  if format != ErrorFormat::ProblemJson {
+    return response;
+  }
+
+  let (parts, body) = response.into_parts();
+  let output = parts.extensions.get::<AppErrorOutput>().cloned();
+  match output {
+    Some(output) if ErrorFormat::of(&parts.headers) == Some(ErrorFormat::Json) => {
+      format.render(parts, output)
+    }
+    _ => Response::from_parts(parts, body),
+  }
+}
+
+/// Renders `output` as the body of an error response, in the format it was already rendered in,
+/// so the middlewares that change the `AppErrorOutput` can be layered in any order around
+/// `error_format`. Bodies that are not an `AppErrorOutput` rendering are kept as they are.
+pub(crate) fn rerender(parts: Parts, body: Body, output: AppErrorOutput) -> Response {
+  match ErrorFormat::of(&parts.headers) {
+    Some(format) => format.render(parts, output),
+    None => Response::from_parts(parts, body),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::{middleware::from_fn_with_state, routing::get, Router};
+  use serde_json::json;
+  use tower::ServiceExt;
+
+  use super::*;
+  use crate::{
+    error::AppError,
+    locale::{localize_errors, MessageCatalog, Messages},
+  };
+
+  async fn get_todo() -> Result<(), AppError> {
+    Err(AppError::NotFound)
+  }
+
+  /// `(Content-Type, Content-Language, body)` of `GET /todo` in French problem details.
+  async fn sent(router: Router) -> (String, String, Value) {
+    let request = Request::get("/todo")
+      .header(header::ACCEPT, PROBLEM_JSON)
+      .header(header::ACCEPT_LANGUAGE, "fr")
+      .body(Body::empty())
+      .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let header = |name| response.headers()[name].to_str().unwrap().to_owned();
+    let (content_type, language) = (
+      header(header::CONTENT_TYPE),
+      header(header::CONTENT_LANGUAGE),
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+      .await
+      .unwrap();
+    (
+      content_type,
+      language,
+      serde_json::from_slice(&body).unwrap(),
+    )
+  }
+
+  #[tokio::test]
+  async fn renders_the_same_in_any_layer_order() {
+    let catalog = MessageCatalog::default().language(
+      "fr",
+      Messages::default().code(AppErrorCode::NotFound, "Introuvable"),
+    );
+    let localize = from_fn_with_state(catalog, localize_errors);
+    let format = from_fn_with_state(ErrorFormat::Json, error_format);
+    let router = Router::new().route("/todo", get(get_todo));
+
+    for router in [
+      router.clone().layer(localize.clone()).layer(format.clone()),
+      router.layer(format).layer(localize),
+    ] {
+      let (content_type, language, body) = sent(router).await;
+      assert_eq!(content_type, PROBLEM_JSON);
+      assert_eq!(language, "fr");
+      assert_eq!(body["title"], "Introuvable");
+      assert_eq!(body["code"], "NOT_FOUND");
+    }
+  }
+
+  fn detail(code: AppErrorCode, details: Option<Value>) -> Option<String> {
+    ProblemDetails::from(AppErrorOutput::new(code, "", details)).detail
+  }
+
+  #[test]
+  fn explains_the_occurrence_from_the_details() {
+    let reason = json!("the last active admin key can not be revoked");
+    assert_eq!(
+      detail(AppErrorCode::Conflict, Some(reason)).as_deref(),
+      Some("the last active admin key can not be revoked")
+    );
+    let fields = json!([
+      { "pointer": "/name", "code": "length", "message": "too short", "params": {} },
+      { "pointer": "", "code": "schema", "message": "dates out of order", "params": {} },
+      { "pointer": "/tag", "code": "custom", "params": {} },
+    ]);
+    assert_eq!(
+      detail(AppErrorCode::Validation, Some(fields)).as_deref(),
+      Some("/name: too short; dates out of order")
+    );
+    let debug = json!({ "details": "gone", "debug": { "source_chain": [] } });
+    assert_eq!(
+      detail(AppErrorCode::Conflict, Some(debug)).as_deref(),
+      Some("gone")
+    );
+  }
+
+  #[test]
+  fn omits_the_detail_without_an_explanation() {
+    assert_eq!(detail(AppErrorCode::NotFound, None), None);
+    let details = json!({ "retry_after": 3 });
+    assert_eq!(detail(AppErrorCode::Internal, Some(details)), None);
+  }
+}
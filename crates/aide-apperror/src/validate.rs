@@ -0,0 +1,20 @@
+use crate::error::AppError;
+
+/// Validation checks that need the state of the app, e.g. that a description is unique, run by
+/// `ValidatedWithState` after those of `Validate`.
+///
+/// Failing checks are reported like those of `Validate`, as `ValidationErrors` converted into an
+/// `AppError::Validation`. Other failures, e.g. a lookup that could not be made, are sent as is.
+///
+/// ```ignore
+/// #[axum::async_trait]
+/// impl ValidateWithState<AppState> for NewTodo {
+///   async fn validate_with_state(&self, app: &AppState) -> Result<(), AppError> {
+///     ..
+///   }
+/// }
+/// ```
+#[axum::async_trait]
+pub trait ValidateWithState<S: Sync>: Sync {
+  async fn validate_with_state(&self, state: &S) -> Result<(), AppError>;
+}
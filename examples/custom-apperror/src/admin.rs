@@ -0,0 +1,135 @@
+use aide::{
+  axum::{
+    routing::{delete_with, get_with, post_with},
+    ApiRouter,
+  },
+  transform::TransformOperation,
+};
+use aide_apperror::{
+  codes::{Conflict, Internal, NotFound},
+  ApiErrors, AppError, ErrorRecord,
+};
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{Duration, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+  extractors::{AdminKey, JsonValidate, PathValidate},
+  keys::ApiKeyInfo,
+  state::AppState,
+};
+
+pub fn admin_routes() -> ApiRouter<AppState> {
+  ApiRouter::new()
+    .api_route("/errors/:error_id", get_with(get_error, get_error_docs))
+    .api_route(
+      "/keys",
+      post_with(create_key, create_key_docs).get_with(list_keys, list_keys_docs),
+    )
+    .api_route("/keys/:id", delete_with(revoke_key, revoke_key_docs))
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+struct SelectError {
+  /// The `error_id` of an error response.
+  error_id: Uuid,
+}
+
+async fn get_error(
+  State(app): State<AppState>,
+  _: AdminKey,
+  PathValidate(select): PathValidate<SelectError>,
+) -> Result<Json<ErrorRecord>, ApiErrors<NotFound>> {
+  match app.errors.get(select.error_id) {
+    Some(record) => Ok(Json(record)),
+    None => Err(AppError::NotFound.into()),
+  }
+}
+
+fn get_error_docs(op: TransformOperation) -> TransformOperation {
+  op.description("Look up what happened for the `error_id` of an error response.")
+    .tag("admin")
+    .security_requirement("ApiKey")
+}
+
+/// New API key details.
+#[derive(Deserialize, JsonSchema, Validate)]
+struct NewApiKey {
+  /// What the key is used for.
+  #[validate(length(min = 1, max = 100))]
+  name: String,
+  /// Whether the key can use the `/admin` routes.
+  #[serde(default)]
+  admin: bool,
+  /// The number of days the key is accepted for, forever if absent.
+  #[validate(range(min = 1, max = 3650))]
+  expires_in_days: Option<u32>,
+}
+
+/// A new API key, the only time it is shown.
+#[derive(Serialize, JsonSchema)]
+struct CreatedApiKey {
+  /// The key to send as `X-Auth-Key`, it can not be retrieved again.
+  key: String,
+  #[serde(flatten)]
+  info: ApiKeyInfo,
+}
+
+async fn create_key(
+  State(app): State<AppState>,
+  AdminKey(admin): AdminKey,
+  JsonValidate(new): JsonValidate<NewApiKey>,
+) -> Result<(StatusCode, Json<CreatedApiKey>), ApiErrors<Internal>> {
+  let expires_at = new
+    .expires_in_days
+    .map(|days| Utc::now() + Duration::days(days.into()));
+  let (info, key) = app
+    .api_keys
+    .create(new.name, new.admin, Some(admin.id), expires_at)
+    .map_err(AppError::internal)?;
+  Ok((StatusCode::CREATED, Json(CreatedApiKey { key, info })))
+}
+
+fn create_key_docs(op: TransformOperation) -> TransformOperation {
+  op.description("Create an API key, only its hash is kept.")
+    .tag("admin")
+    .security_requirement("ApiKey")
+    .response::<201, Json<CreatedApiKey>>()
+}
+
+async fn list_keys(State(app): State<AppState>, _: AdminKey) -> Json<Vec<ApiKeyInfo>> {
+  Json(app.api_keys.list())
+}
+
+fn list_keys_docs(op: TransformOperation) -> TransformOperation {
+  op.description("List every API key, including revoked and expired ones.")
+    .tag("admin")
+    .security_requirement("ApiKey")
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+struct SelectKey {
+  /// The ID of the API key.
+  id: Uuid,
+}
+
+async fn revoke_key(
+  State(app): State<AppState>,
+  _: AdminKey,
+  PathValidate(select): PathValidate<SelectKey>,
+) -> Result<StatusCode, ApiErrors<(NotFound, Conflict, Internal)>> {
+  app.api_keys.revoke(select.id)?;
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn revoke_key_docs(op: TransformOperation) -> TransformOperation {
+  op.description(
+    "Revoke an API key, it is no longer accepted. The last active admin key can not be revoked.",
+  )
+  .tag("admin")
+  .security_requirement("ApiKey")
+  .response_with::<204, (), _>(|res| res.description("The API key has been revoked."))
+}
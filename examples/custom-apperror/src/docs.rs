@@ -1,10 +1,17 @@
 use std::sync::Arc;
 
 use aide::{openapi::OpenApi, redoc::Redoc, scalar::Scalar};
-use axum::{response::IntoResponse, routing::get, Extension, Json, Router};
+use aide_apperror::{error_catalog, error_reference_html, error_reference_markdown, ErrorPolicy};
+use axum::{
+  extract::State,
+  http::header,
+  response::{Html, IntoResponse},
+  routing::get,
+  Extension, Json, Router,
+};
 
-pub fn docs_routes() -> Router {
-  let router = Router::new()
+pub fn docs_routes(error_policy: ErrorPolicy) -> Router {
+  Router::new()
     .route(
       "/",
       get(
@@ -21,9 +28,18 @@ pub fn docs_routes() -> Router {
           .axum_handler(),
       ),
     )
-    .route("/private/api.json", get(serve_docs));
-
-  router
+    .route("/private/api.json", get(serve_docs))
+    // Every `AppErrorCode` that can be sent, with its status, description and example
+    .route(
+      "/errors",
+      get(|State(policy): State<ErrorPolicy>| async move { Json(error_catalog(&policy)) }),
+    )
+    .route(
+      "/errors.html",
+      get(|State(policy): State<ErrorPolicy>| async move { Html(error_reference_html(&policy)) }),
+    )
+    .route("/errors.md", get(serve_error_reference_markdown))
+    .with_state(error_policy)
 }
 
 async fn serve_docs(Extension(api): Extension<Arc<OpenApi>>) -> impl IntoResponse {
@@ -31,3 +47,10 @@ async fn serve_docs(Extension(api): Extension<Arc<OpenApi>>) -> impl IntoRespons
   // no `Component` will be generated from this route
   Json(api).into_response()
 }
+
+async fn serve_error_reference_markdown(State(policy): State<ErrorPolicy>) -> impl IntoResponse {
+  (
+    [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+    error_reference_markdown(&policy),
+  )
+}
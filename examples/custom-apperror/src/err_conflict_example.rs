@@ -0,0 +1,56 @@
+//! `PathValidate` and `JsonValidate` both reject with a 400 (`PathRejection`, `JsonSyntaxError`)
+//! and a 422 (`Validation`, `JsonDataError`). Rather than an `InferredResponseConflict`, each
+//! status is documented once, with an example per error code.
+
+use aide::{
+  axum::{routing::put_with, ApiRouter},
+  transform::TransformOperation,
+};
+use aide_apperror::{codes::NotFound, ApiErrors, AppError};
+use axum::{extract::State, http::StatusCode};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+  extractors::{ApiKey, JsonValidate, PathValidate},
+  state::AppState,
+};
+
+pub fn err_conflict_routes() -> ApiRouter<AppState> {
+  ApiRouter::new().api_route("/:id", put_with(rename_todo, rename_todo_docs))
+}
+
+#[derive(Deserialize, JsonSchema, Validate)]
+struct SelectTodo {
+  /// The ID of the Todo.
+  id: Uuid,
+}
+
+/// New description of a Todo.
+#[derive(Deserialize, JsonSchema, Validate)]
+struct RenameTodo {
+  /// The new description.
+  #[validate(length(min = 5))]
+  description: String,
+}
+
+async fn rename_todo(
+  State(app): State<AppState>,
+  _: ApiKey,
+  PathValidate(todo): PathValidate<SelectTodo>,
+  JsonValidate(rename): JsonValidate<RenameTodo>,
+) -> Result<StatusCode, ApiErrors<NotFound>> {
+  if let Some(todo) = app.todos.lock().unwrap().get_mut(&todo.id) {
+    todo.description = rename.description;
+    Ok(StatusCode::NO_CONTENT)
+  } else {
+    Err(AppError::NotFound.into())
+  }
+}
+
+fn rename_todo_docs(op: TransformOperation) -> TransformOperation {
+  op.description("Rename a Todo, documenting the merged 400 and 422 responses.")
+    .response_with::<204, (), _>(|res| res.description("The Todo has been renamed."))
+}
@@ -2,72 +2,113 @@ use aide::{
   openapi::{Parameter, ParameterData},
   operation::add_parameters,
 };
+use aide_apperror::{AppError, AppErrorCode};
 use axum::{
-  extract::FromRequestParts,
-  http::{request::Parts, HeaderMap},
+  extract::{FromRef, FromRequestParts},
+  http::request::Parts,
 };
 use indexmap::IndexMap;
 
-use crate::error::{AppError, AppErrorCode};
+use crate::keys::{ApiKeyInfo, ApiKeyStore};
 
-/// Rejects if `X-Auth-Key` is invalid or missing from headers
-pub struct ApiKey();
+/// Rejects if `X-Auth-Key` is missing from headers, or is not an active key of the `ApiKeyStore`
+pub struct ApiKey(pub ApiKeyInfo);
+
+/// Rejects like `ApiKey`, and if the key is not an admin key
+pub struct AdminKey(pub ApiKeyInfo);
+
+fn authenticate(parts: &Parts, store: &ApiKeyStore) -> Result<ApiKeyInfo, AppError> {
+  let value = parts
+    .headers
+    .get("X-Auth-Key")
+    .ok_or(AppError::AuthKeyMissing)?;
+  let key = value.to_str().map_err(|_| AppError::AuthKeyInvalid)?;
+  store.authenticate(key).ok_or(AppError::AuthKeyInvalid)
+}
 
 #[axum::async_trait]
 impl<S> FromRequestParts<S> for ApiKey
 where
+  ApiKeyStore: FromRef<S>,
   S: Send + Sync,
 {
   type Rejection = AppError;
 
   async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-    let headers = unsafe {
-      HeaderMap::from_request_parts(parts, state)
-        .await
-        .unwrap_unchecked()
-    };
-    let value = headers.get("X-Auth-Key").ok_or(AppError::AuthKeyMissing)?;
-    match value.as_bytes() {
-      b"CORRECT_API_KEY" => (),
-      b"CORRECT_API_KEY_BUT_NO_ACCESS" => return Err(AppError::AuthKeyNoAccess),
-      _ => return Err(AppError::AuthKeyInvalid),
-    }
+    authenticate(parts, &ApiKeyStore::from_ref(state)).map(Self)
+  }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for AdminKey
+where
+  ApiKeyStore: FromRef<S>,
+  S: Send + Sync,
+{
+  type Rejection = AppError;
 
-    Ok(Self())
+  async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+    let key = authenticate(parts, &ApiKeyStore::from_ref(state))?;
+    if !key.admin {
+      return Err(AppError::AuthKeyNoAccess);
+    }
+    Ok(Self(key))
   }
 }
 
 impl aide::OperationInput for ApiKey {
   fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
-    add_parameters(
+    document_key(
       ctx,
       operation,
-      [Parameter::Header {
-        parameter_data: ParameterData {
-          name: "X-Auth-Key".to_owned(),
-          description: None,
-          required: true,
-          deprecated: None,
-          format: aide::openapi::ParameterSchemaOrContent::Schema(aide::openapi::SchemaObject {
-            json_schema: schemars::schema::Schema::Object(schemars::schema_for!(String).schema),
-            external_docs: None,
-            example: None,
-          }),
-          example: None,
-          examples: IndexMap::new(),
-          explode: None,
-          extensions: IndexMap::new(),
-        },
-        style: aide::openapi::HeaderStyle::Simple,
-      }],
+      [AppErrorCode::AuthKeyInvalid, AppErrorCode::AuthKeyMissing],
     );
+  }
+}
 
-    [
-      AppErrorCode::AuthKeyInvalid,
-      AppErrorCode::AuthKeyMissing,
-      AppErrorCode::AuthKeyNoAccess,
-    ]
-    .into_iter()
-    .for_each(|status| super::set_inferred_response(ctx, operation, status));
+impl aide::OperationInput for AdminKey {
+  fn operation_input(ctx: &mut aide::gen::GenContext, operation: &mut aide::openapi::Operation) {
+    document_key(
+      ctx,
+      operation,
+      [
+        AppErrorCode::AuthKeyInvalid,
+        AppErrorCode::AuthKeyMissing,
+        AppErrorCode::AuthKeyNoAccess,
+      ],
+    );
   }
 }
+
+fn document_key(
+  ctx: &mut aide::gen::GenContext,
+  operation: &mut aide::openapi::Operation,
+  codes: impl IntoIterator<Item = AppErrorCode>,
+) {
+  add_parameters(
+    ctx,
+    operation,
+    [Parameter::Header {
+      parameter_data: ParameterData {
+        name: "X-Auth-Key".to_owned(),
+        description: None,
+        required: true,
+        deprecated: None,
+        format: aide::openapi::ParameterSchemaOrContent::Schema(aide::openapi::SchemaObject {
+          json_schema: schemars::schema::Schema::Object(schemars::schema_for!(String).schema),
+          external_docs: None,
+          example: None,
+        }),
+        example: None,
+        examples: IndexMap::new(),
+        explode: None,
+        extensions: IndexMap::new(),
+      },
+      style: aide::openapi::HeaderStyle::Simple,
+    }],
+  );
+
+  codes
+    .into_iter()
+    .for_each(|status| aide_apperror::set_inferred_response(ctx, operation, status));
+}
@@ -0,0 +1,193 @@
+use std::{
+  fs::{self, File},
+  io::{self, BufReader, BufWriter, Write},
+  path::PathBuf,
+  sync::{Arc, Mutex, MutexGuard},
+};
+
+use aide_apperror::AppError;
+use axum::extract::FromRef;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// The prefix of every API key, to recognize them e.g. in logs or secret scanners.
+const KEY_PREFIX: &str = "tdk_";
+
+/// What is known about an API key, never its secret.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApiKeyInfo {
+  /// The ID of the key, to revoke it with.
+  pub id: Uuid,
+  /// What the key is used for.
+  pub name: String,
+  /// Whether the key can use the `/admin` routes.
+  pub admin: bool,
+  pub created_at: DateTime<Utc>,
+  /// The ID of the admin key that created it, absent for those created on startup.
+  pub created_by: Option<Uuid>,
+  /// When the key stops being accepted, never if absent.
+  pub expires_at: Option<DateTime<Utc>>,
+  /// Whether the key was revoked, it is no longer accepted.
+  pub revoked: bool,
+}
+
+impl ApiKeyInfo {
+  fn is_active(&self) -> bool {
+    !self.revoked
+      && self
+        .expires_at
+        .is_none_or(|expires_at| expires_at > Utc::now())
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredKey {
+  #[serde(flatten)]
+  info: ApiKeyInfo,
+  /// The hex encoded SHA-256 of the key, the key itself is never stored.
+  hash: String,
+}
+
+/// The API keys accepted by `ApiKey`, optionally persisted as a json file.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyStore(Arc<StoreInner>);
+
+#[derive(Debug, Default)]
+struct StoreInner {
+  path: Option<PathBuf>,
+  keys: Mutex<Vec<StoredKey>>,
+}
+
+impl FromRef<AppState> for ApiKeyStore {
+  fn from_ref(app: &AppState) -> Self {
+    app.api_keys.clone()
+  }
+}
+
+impl ApiKeyStore {
+  /// Load the keys at `path`, it is created on the first change.
+  pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+    let path = path.into();
+    let keys = if path.exists() {
+      serde_json::from_reader(BufReader::new(File::open(&path)?))?
+    } else {
+      Vec::new()
+    };
+    Ok(Self(Arc::new(StoreInner {
+      path: Some(path),
+      keys: Mutex::new(keys),
+    })))
+  }
+
+  /// Whether an admin key is still accepted, to create a new one if not.
+  pub fn has_active_admin(&self) -> bool {
+    self
+      .keys()
+      .iter()
+      .any(|key| key.info.admin && key.info.is_active())
+  }
+
+  /// Create a key, returned along with its info. It can not be recovered afterwards.
+  pub fn create(
+    &self,
+    name: String,
+    admin: bool,
+    created_by: Option<Uuid>,
+    expires_at: Option<DateTime<Utc>>,
+  ) -> io::Result<(ApiKeyInfo, String)> {
+    let mut secret = [0; 32];
+    getrandom::getrandom(&mut secret).map_err(io::Error::other)?;
+    let key = format!("{KEY_PREFIX}{}", hex(&secret));
+
+    let info = ApiKeyInfo {
+      id: Uuid::new_v4(),
+      name,
+      admin,
+      created_at: Utc::now(),
+      created_by,
+      expires_at,
+      revoked: false,
+    };
+    let mut keys = self.keys();
+    keys.push(StoredKey {
+      info: info.clone(),
+      hash: hash(&key),
+    });
+    if let Err(e) = self.persist(&keys) {
+      keys.pop();
+      return Err(e);
+    }
+    Ok((info, key))
+  }
+
+  pub fn list(&self) -> Vec<ApiKeyInfo> {
+    self.keys().iter().map(|key| key.info.clone()).collect()
+  }
+
+  /// Revoke the key with `id`, unless it is the last active admin key.
+  pub fn revoke(&self, id: Uuid) -> Result<ApiKeyInfo, AppError> {
+    let mut keys = self.keys();
+    let Some(index) = keys.iter().position(|key| key.info.id == id) else {
+      return Err(AppError::NotFound);
+    };
+    if keys[index].info.revoked {
+      return Ok(keys[index].info.clone());
+    }
+    let other_admin = keys
+      .iter()
+      .any(|key| key.info.id != id && key.info.admin && key.info.is_active());
+    if keys[index].info.admin && keys[index].info.is_active() && !other_admin {
+      return Err(AppError::Conflict(
+        "the last active admin key can not be revoked".into(),
+      ));
+    }
+
+    keys[index].info.revoked = true;
+    if let Err(e) = self.persist(&keys) {
+      keys[index].info.revoked = false;
+      return Err(AppError::internal(e));
+    }
+    Ok(keys[index].info.clone())
+  }
+
+  /// The info of `key`, if it is known, not revoked and not expired.
+  pub fn authenticate(&self, key: &str) -> Option<ApiKeyInfo> {
+    let hash = hash(key);
+    self
+      .keys()
+      .iter()
+      .find(|stored| stored.hash == hash)
+      .map(|stored| stored.info.clone())
+      .filter(ApiKeyInfo::is_active)
+  }
+
+  fn keys(&self) -> MutexGuard<'_, Vec<StoredKey>> {
+    self.0.keys.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  fn persist(&self, keys: &[StoredKey]) -> io::Result<()> {
+    let Some(path) = &self.0.path else {
+      return Ok(());
+    };
+    // replaces the store at once, a failed write leaves the previous keys in place
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = BufWriter::new(File::create(&tmp_path)?);
+    serde_json::to_writer_pretty(&mut file, keys)?;
+    file.flush()?;
+    file.get_ref().sync_all()?;
+    fs::rename(&tmp_path, path)
+  }
+}
+
+fn hash(key: &str) -> String {
+  hex(&Sha256::digest(key.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -1,33 +1,105 @@
 use std::sync::Arc;
 
 use aide::{axum::ApiRouter, openapi::OpenApi, transform::TransformOpenApi};
-use axum::Extension;
+use aide_apperror::{
+  apply_error_policy, catch_panic, debug_errors, document_panics, error_format, localize_errors,
+  record_errors, route_matched_path, AppErrorCode, ErrorFormat, ErrorJournal, ErrorPolicy,
+  Redaction,
+};
+use axum::{
+  http::HeaderName,
+  middleware::{from_fn, from_fn_with_state},
+  Extension,
+};
 use docs::docs_routes;
 
-use crate::{state::AppState, todos::routes::todo_routes};
+use crate::{
+  admin::admin_routes, err_conflict_example::err_conflict_routes, keys::ApiKeyStore,
+  messages::message_catalog, state::AppState, todos::routes::todo_routes,
+};
 
+mod admin;
 mod docs;
 mod err_conflict_example;
-mod error;
 mod extractors;
+mod keys;
+mod messages;
 mod state;
 mod todos;
 
 #[tokio::main]
 async fn main() {
+  // Logs the source of internal errors, by their `error_id`
+  tracing_subscriber::fmt::init();
+
   aide::gen::on_error(|error| {
     println!("{error}");
   });
 
-  let state = AppState::default();
+  // `HIDE_NO_ACCESS=1` answers callers without access, e.g. to the Todo items of other keys, as if
+  // the resource did not exist
+  let mut error_policy = ErrorPolicy::default();
+  if std::env::var_os("HIDE_NO_ACCESS").is_some() {
+    error_policy = error_policy.remap(AppErrorCode::AuthKeyNoAccess, AppErrorCode::NotFound);
+  }
+  let api_keys = ApiKeyStore::open("api_keys.json").expect("failed to open API key store");
+  // e.g. on the first start, or once every admin key expired
+  if !api_keys.has_active_admin() {
+    let (_, key) = api_keys
+      .create("admin".into(), true, None, None)
+      .expect("failed to create an admin API key");
+    println!("Created an admin API key, it will not be shown again: {key}");
+  }
+  let state = AppState {
+    errors: ErrorJournal::open(
+      "error_journal.jsonl",
+      1000,
+      Redaction::default().header(HeaderName::from_static("x-auth-key")),
+    )
+    .expect("failed to open error journal"),
+    error_policy,
+    api_keys,
+    ..Default::default()
+  };
   let mut api = OpenApi::default();
 
+  // Lets the journal record the route of errors, e.g. `/todo/:id`
+  let matched_path = || from_fn(route_matched_path);
   let app = ApiRouter::new()
-    .nest_api_service("/todo", todo_routes().with_state(state))
+    .nest_api_service(
+      "/todo",
+      todo_routes()
+        .route_layer(matched_path())
+        .with_state(state.clone()),
+    )
+    .nest_api_service(
+      "/conflict",
+      err_conflict_routes()
+        .route_layer(matched_path())
+        .with_state(state.clone()),
+    )
+    .nest_api_service(
+      "/admin",
+      admin_routes()
+        .route_layer(matched_path())
+        .with_state(state.clone()),
+    )
     // .finish_api(&mut api)
-    .finish_api_with(&mut api, api_docs)
-    .nest_service("/docs", docs_routes())
-    .layer(Extension(Arc::new(api)));
+    .finish_api_with(&mut api, |api| {
+      api_docs(api).with(|api| state.error_policy.document(api))
+    })
+    .nest_service("/docs", docs_routes(state.error_policy.clone()))
+    // A panic, e.g. on a poisoned `todos` lock, becomes an `AppError::Internal` response
+    .layer(catch_panic())
+    // `APP_ERROR_DEBUG=1` adds the source chain, backtrace and rejecting extractor to errors
+    .layer(from_fn(debug_errors))
+    .layer(from_fn_with_state(state.error_policy, apply_error_policy))
+    .layer(Extension(Arc::new(api)))
+    .layer(from_fn_with_state(state.errors, record_errors))
+    // `Accept-Language: fr` translates error messages, the journal keeps them in English
+    .layer(from_fn_with_state(message_catalog(), localize_errors))
+    // `Accept: application/problem+json` switches errors to RFC 9457 problem details
+    .layer(from_fn_with_state(ErrorFormat::Json, error_format));
 
   println!("Example docs are accessible at http://127.0.0.1:3001/docs");
 
@@ -47,15 +119,23 @@ fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
       description: Some("Todo Management".into()),
       ..Default::default()
     })
+    .tag(aide::openapi::Tag {
+      name: "admin".into(),
+      description: Some("Administration".into()),
+      ..Default::default()
+    })
     .security_scheme(
       "ApiKey",
       aide::openapi::SecurityScheme::ApiKey {
         location: aide::openapi::ApiKeyLocation::Header,
         name: "X-Auth-Key".into(),
-        description: Some("A key that is ignored.".into()),
+        description: Some(
+          "A key created by `POST /admin/keys`, admin keys created on startup are printed.".into(),
+        ),
         extensions: Default::default(),
       },
     )
+    .with(document_panics)
   // .default_response::<error::AppError>()
   // .default_response_with::<axum::Json<error::AppError>, _>(|res| {
   //     res.example(error::AppError {
@@ -0,0 +1,64 @@
+use aide_apperror::{locale::DEFAULT_LANGUAGE, AppErrorCode, MessageCatalog, Messages};
+
+/// Error messages in the languages of the app, English ones are built into `AppError`.
+pub fn message_catalog() -> MessageCatalog {
+  MessageCatalog::new(DEFAULT_LANGUAGE).language(
+    "fr",
+    Messages::default()
+      .code(AppErrorCode::Internal, "Erreur interne du serveur")
+      .code(AppErrorCode::Validation, "Échec de la validation")
+      .code(AppErrorCode::PathRejection, "Chemin invalide")
+      .code(
+        AppErrorCode::QueryRejection,
+        "Paramètres de requête invalides",
+      )
+      .code(AppErrorCode::HeaderRejection, "En-têtes invalides")
+      .code(
+        AppErrorCode::MissingJsonContentType,
+        "Type de média non pris en charge",
+      )
+      .code(AppErrorCode::JsonSyntaxError, "JSON invalide")
+      .code(AppErrorCode::JsonDataError, "Données JSON invalides")
+      .code(
+        AppErrorCode::JsonPayloadTooLarge,
+        "Requête trop volumineuse",
+      )
+      .code(AppErrorCode::JsonRejection, "Requête JSON invalide")
+      .code(
+        AppErrorCode::MissingFormContentType,
+        "Type de média non pris en charge",
+      )
+      .code(
+        AppErrorCode::FormDataError,
+        "Données de formulaire invalides",
+      )
+      .code(
+        AppErrorCode::FormPayloadTooLarge,
+        "Requête trop volumineuse",
+      )
+      .code(AppErrorCode::FormRejection, "Formulaire invalide")
+      .code(AppErrorCode::MultipartRejection, "Formulaire invalide")
+      .code(AppErrorCode::MultipartError, "Formulaire invalide")
+      .code(
+        AppErrorCode::MultipartPayloadTooLarge,
+        "Requête trop volumineuse",
+      )
+      .code(
+        AppErrorCode::MultipartDataError,
+        "Données de formulaire invalides",
+      )
+      .code(AppErrorCode::FileTooLarge, "Fichier trop volumineux")
+      .code(
+        AppErrorCode::UnsupportedFileType,
+        "Type de fichier non pris en charge",
+      )
+      .code(AppErrorCode::AuthKeyMissing, "Non autorisé")
+      .code(AppErrorCode::AuthKeyInvalid, "Non autorisé")
+      .code(AppErrorCode::AuthKeyNoAccess, "Accès refusé")
+      .code(AppErrorCode::NotFound, "Introuvable")
+      .code(AppErrorCode::Conflict, "Conflit")
+      .rule("length", "longueur invalide")
+      .rule("range", "doit être compris entre {min} et {max}")
+      .rule("unique", "une tâche non terminée a déjà cette description"),
+  )
+}
@@ -3,11 +3,15 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use aide_apperror::{ErrorJournal, ErrorPolicy};
 use uuid::Uuid;
 
-use crate::todos::TodoItem;
+use crate::{keys::ApiKeyStore, todos::TodoItem};
 
 #[derive(Debug, Clone, Default)]
 pub struct AppState {
     pub todos: Arc<Mutex<HashMap<Uuid, TodoItem>>>,
+    pub errors: ErrorJournal,
+    pub error_policy: ErrorPolicy,
+    pub api_keys: ApiKeyStore,
 }
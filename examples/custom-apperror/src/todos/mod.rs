@@ -12,4 +12,6 @@ pub struct TodoItem {
     pub description: String,
     /// Whether the item was completed.
     pub complete: bool,
+    /// The ID of the API key that created it, only that key and admin keys can access it.
+    pub owner: Uuid,
 }
@@ -1,18 +1,32 @@
+use std::{collections::HashMap, sync::MutexGuard};
+
 use aide::{
   axum::{
     routing::{get_with, post_with, put_with},
-    ApiRouter, IntoApiResponse,
+    ApiRouter,
   },
   transform::TransformOperation,
 };
-use axum::{extract::State, http::StatusCode, Json};
+use aide_apperror::{
+  codes::{AuthKeyNoAccess, Internal, NotFound, Validation},
+  ApiErrors, AppError, FileRule, MultipartForm, RequestSources, UploadedFile, ValidateWithState,
+};
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError, ValidationErrors};
 
 use crate::{
-  extractors::{auth::ApiKey, JsonValidate, PathValidate},
+  extractors::{
+    ApiKey, JsonValidate, MultipartValidate, PathValidate, QueryValidate, RequestValidate,
+    Validated, ValidatedWithState,
+  },
+  keys::ApiKeyInfo,
   state::AppState,
 };
 
@@ -26,18 +40,72 @@ pub fn todo_routes() -> ApiRouter<AppState> {
     )
     .api_route(
       "/:id",
-      get_with(get_todo, get_todo_docs).delete_with(delete_todo, delete_todo_docs),
+      get_with(get_todo, get_todo_docs)
+        .put_with(rename_todo, rename_todo_docs)
+        .delete_with(delete_todo, delete_todo_docs),
     )
     .api_route("/:id/complete", put_with(complete_todo, complete_todo_docs))
+    .api_route(
+      "/:id/attachments",
+      post_with(attach_to_todo, attach_to_todo_docs),
+    )
 }
 
 /// New Todo details.
 #[derive(Deserialize, JsonSchema, Validate)]
 struct NewTodo {
-  /// The description for the new Todo.
+  /// The description for the new Todo, unique among incomplete ones.
   description: String,
 }
 
+#[axum::async_trait]
+impl ValidateWithState<AppState> for NewTodo {
+  async fn validate_with_state(&self, app: &AppState) -> Result<(), AppError> {
+    let todos = lock_todos(app)?;
+    unique_description(todos.values(), &self.description)?;
+    Ok(())
+  }
+}
+
+fn lock_todos(app: &AppState) -> Result<MutexGuard<'_, HashMap<Uuid, TodoItem>>, AppError> {
+  app
+    .todos
+    .lock()
+    .map_err(|_| AppError::internal("todos lock is poisoned"))
+}
+
+/// The Todo `id`, if `key` created it or is an admin key.
+///
+/// With the `HIDE_NO_ACCESS` policy, `AuthKeyNoAccess` is sent as `NotFound`, so other keys can
+/// not tell whether the Todo exists.
+fn accessible_todo<'a>(
+  todos: &'a mut HashMap<Uuid, TodoItem>,
+  id: &Uuid,
+  key: &ApiKeyInfo,
+) -> Result<&'a mut TodoItem, AppError> {
+  let todo = todos.get_mut(id).ok_or(AppError::NotFound)?;
+  if todo.owner != key.id && !key.admin {
+    return Err(AppError::AuthKeyNoAccess);
+  }
+  Ok(todo)
+}
+
+/// Fails if one of the incomplete `todos` already has `description`.
+fn unique_description<'a>(
+  mut todos: impl Iterator<Item = &'a TodoItem>,
+  description: &str,
+) -> Result<(), ValidationErrors> {
+  if todos.any(|todo| !todo.complete && todo.description == description) {
+    let mut error = ValidationError::new("unique");
+    error.message = Some("an incomplete Todo already has this description".into());
+    error.add_param("value".into(), &description);
+    let mut errors = ValidationErrors::new();
+    errors.add("description", error);
+    return Err(errors);
+  }
+  Ok(())
+}
+
 /// New Todo details.
 #[derive(Serialize, JsonSchema)]
 struct TodoCreated {
@@ -47,20 +115,24 @@ struct TodoCreated {
 
 async fn create_todo(
   State(app): State<AppState>,
-  _: ApiKey,
-  JsonValidate(todo): JsonValidate<NewTodo>,
-) -> impl IntoApiResponse {
+  ApiKey(key): ApiKey,
+  ValidatedWithState(JsonValidate(todo)): ValidatedWithState<JsonValidate<NewTodo>>,
+) -> Result<(StatusCode, Json<TodoCreated>), ApiErrors<(Validation, Internal)>> {
+  let mut todos = lock_todos(&app)?;
+  // checked again under the lock, another request may have taken the description since
+  unique_description(todos.values(), &todo.description)?;
   let id = Uuid::new_v4();
-  app.todos.lock().unwrap().insert(
+  todos.insert(
     id,
     TodoItem {
       complete: false,
       description: todo.description,
       id,
+      owner: key.id,
     },
   );
 
-  Json(TodoCreated { id })
+  Ok((StatusCode::CREATED, Json(TodoCreated { id })))
 }
 
 fn create_todo_docs(op: TransformOperation) -> TransformOperation {
@@ -73,10 +145,32 @@ struct TodoList {
   todo_ids: Vec<Uuid>,
 }
 
-async fn list_todos(State(app): State<AppState>) -> Json<TodoList> {
-  Json(TodoList {
-    todo_ids: app.todos.lock().unwrap().keys().copied().collect(),
-  })
+/// Filters of the Todo list.
+#[derive(Deserialize, JsonSchema, Validate)]
+struct ListTodos {
+  /// Only list Todo items that are, or are not, complete.
+  complete: Option<bool>,
+  /// The maximum number of Todo items to list.
+  #[validate(range(min = 1, max = 100))]
+  limit: Option<usize>,
+}
+
+async fn list_todos(
+  State(app): State<AppState>,
+  QueryValidate(filter): QueryValidate<ListTodos>,
+) -> Json<TodoList> {
+  let todos = app.todos.lock().unwrap();
+  let todo_ids = todos
+    .values()
+    .filter(|todo| {
+      filter
+        .complete
+        .is_none_or(|complete| todo.complete == complete)
+    })
+    .map(|todo| todo.id)
+    .take(filter.limit.unwrap_or(usize::MAX))
+    .collect();
+  Json(TodoList { todo_ids })
 }
 
 fn list_todos_docs(op: TransformOperation) -> TransformOperation {
@@ -91,59 +185,145 @@ struct SelectTodo {
 
 async fn get_todo(
   State(app): State<AppState>,
+  ApiKey(key): ApiKey,
   PathValidate(todo): PathValidate<SelectTodo>,
-) -> Result<Json<TodoItem>, StatusCode> {
-  if let Some(todo) = app.todos.lock().unwrap().get(&todo.id) {
-    Ok(Json(todo.clone()))
-  } else {
-    Err(StatusCode::NOT_FOUND)
-  }
+) -> Result<Json<TodoItem>, ApiErrors<(NotFound, AuthKeyNoAccess, Internal)>> {
+  let mut todos = lock_todos(&app)?;
+  let todo = accessible_todo(&mut todos, &todo.id, &key)?;
+  Ok(Json(todo.clone()))
 }
 
 fn get_todo_docs(op: TransformOperation) -> TransformOperation {
-  op.description("Get a single Todo item.")
+  op.description("Get a single Todo item, created by the API key unless it is an admin key.")
     .response_with::<200, Json<TodoItem>, _>(|res| {
       res.example(TodoItem {
         complete: false,
         description: "fix bugs".into(),
         id: Uuid::nil(),
+        owner: Uuid::nil(),
       })
     })
-    .response_with::<404, (), _>(|res| res.description("todo was not found"))
+}
+
+/// A new description for a Todo.
+#[derive(RequestSources, Validate)]
+struct RenameTodo {
+  /// The ID of the Todo.
+  #[from(path)]
+  id: Uuid,
+  /// The new description, unique among the other incomplete Todo items.
+  #[from(body)]
+  #[validate(length(min = 5))]
+  description: String,
+}
+
+// checks the body against the other Todo items, so needs the id from the path
+#[axum::async_trait]
+impl ValidateWithState<AppState> for RenameTodo {
+  async fn validate_with_state(&self, app: &AppState) -> Result<(), AppError> {
+    let todos = lock_todos(app)?;
+    let others = todos.values().filter(|todo| todo.id != self.id);
+    unique_description(others, &self.description)?;
+    Ok(())
+  }
+}
+
+async fn rename_todo(
+  State(app): State<AppState>,
+  ApiKey(key): ApiKey,
+  ValidatedWithState(RequestValidate(rename)): ValidatedWithState<RequestValidate<RenameTodo>>,
+) -> Result<StatusCode, ApiErrors<(NotFound, AuthKeyNoAccess, Validation, Internal)>> {
+  let mut todos = lock_todos(&app)?;
+  accessible_todo(&mut todos, &rename.id, &key)?;
+  // checked again under the lock, another request may have taken the description since
+  let others = todos.values().filter(|todo| todo.id != rename.id);
+  unique_description(others, &rename.description)?;
+  accessible_todo(&mut todos, &rename.id, &key)?.description = rename.description;
+  Ok(StatusCode::NO_CONTENT)
+}
+
+fn rename_todo_docs(op: TransformOperation) -> TransformOperation {
+  op.description("Rename a Todo.")
+    .response_with::<204, (), _>(|res| res.description("The Todo has been renamed."))
 }
 
 // Can quickly modify Response in fn signature
 async fn delete_todo(
   State(app): State<AppState>,
   PathValidate(todo): PathValidate<SelectTodo>,
-) -> StatusCode {
+) -> Result<StatusCode, ApiErrors<NotFound>> {
   if app.todos.lock().unwrap().remove(&todo.id).is_some() {
-    StatusCode::NO_CONTENT
+    Ok(StatusCode::NO_CONTENT)
   } else {
-    StatusCode::NOT_FOUND
+    Err(AppError::NotFound.into())
   }
 }
 
 fn delete_todo_docs(op: TransformOperation) -> TransformOperation {
   op.description("Delete a Todo item.")
     .response_with::<204, (), _>(|res| res.description("The Todo has been deleted."))
-    .response_with::<404, (), _>(|res| res.description("The todo was not found"))
 }
 
 async fn complete_todo(
   State(app): State<AppState>,
-  PathValidate(todo): PathValidate<SelectTodo>,
-) -> StatusCode {
-  if let Some(todo) = app.todos.lock().unwrap().get_mut(&todo.id) {
+  Validated(Path(todo)): Validated<Path<SelectTodo>>,
+) -> Result<StatusCode, ApiErrors<(NotFound, Internal)>> {
+  let mut todos = lock_todos(&app)?;
+  if let Some(todo) = todos.get_mut(&todo.id) {
     todo.complete = true;
-    StatusCode::NO_CONTENT
+    Ok(StatusCode::NO_CONTENT)
   } else {
-    StatusCode::NOT_FOUND
+    Err(AppError::NotFound.into())
   }
 }
 
 fn complete_todo_docs(op: TransformOperation) -> TransformOperation {
-  op.description("Complete a Todo.")
-    .response::<204, ()>()
-    .response::<404, ()>()
+  op.description("Complete a Todo.").response::<204, ()>()
+}
+
+/// A file to attach to a Todo.
+#[derive(Deserialize, JsonSchema, Validate)]
+struct NewAttachment {
+  /// What the file is about.
+  #[validate(length(max = 200))]
+  note: Option<String>,
+  /// An image or a PDF, of at most 1 MiB.
+  file: UploadedFile,
+}
+
+impl MultipartForm for NewAttachment {
+  const FILE_RULES: &'static [FileRule] = &[FileRule::new("file")
+    .max_size(1024 * 1024)
+    .accept(&["image/*", "application/pdf"])];
+}
+
+/// The attached file.
+#[derive(Serialize, JsonSchema)]
+struct Attachment {
+  file_name: Option<String>,
+  content_type: String,
+  /// The size of the file, in bytes.
+  size: usize,
+  note: Option<String>,
+}
+
+async fn attach_to_todo(
+  State(app): State<AppState>,
+  PathValidate(todo): PathValidate<SelectTodo>,
+  MultipartValidate(attachment): MultipartValidate<NewAttachment>,
+) -> Result<Json<Attachment>, ApiErrors<NotFound>> {
+  if !app.todos.lock().unwrap().contains_key(&todo.id) {
+    return Err(AppError::NotFound.into());
+  }
+  let file = attachment.file;
+  Ok(Json(Attachment {
+    file_name: file.file_name,
+    content_type: file.content_type,
+    size: file.bytes.len(),
+    note: attachment.note,
+  }))
+}
+
+fn attach_to_todo_docs(op: TransformOperation) -> TransformOperation {
+  op.description("Attach a file to a Todo.")
 }
@@ -12,7 +12,7 @@ use aide::{
 use axum::{response::IntoResponse, Extension, Json};
 
 pub fn docs_routes() -> ApiRouter {
-  let router = ApiRouter::new()
+  ApiRouter::new()
     .api_route_with(
       "/",
       get_with(
@@ -33,9 +33,7 @@ pub fn docs_routes() -> ApiRouter {
       ),
       |p| p.security_requirement("ApiKey"),
     )
-    .route("/private/api.json", get(serve_docs));
-
-  router
+    .route("/private/api.json", get(serve_docs))
 }
 
 async fn serve_docs(Extension(api): Extension<Arc<OpenApi>>) -> impl IntoApiResponse {
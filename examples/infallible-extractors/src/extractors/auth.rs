@@ -1,13 +1,15 @@
 use std::convert::Infallible;
 
+use aide_apperror::AppError;
 use axum::{
   extract::FromRequestParts,
   http::{request::Parts, HeaderMap},
 };
 
-use crate::error::AppError;
-
 /// Rejects if `X-Auth-Key` is invalid or missing from headers
+///
+/// The keys are fixed, this example is about infallible extractors. The `custom-apperror` example
+/// stores hashed keys managed through its admin routes.
 #[derive(aide::OperationIo)]
 #[aide(input)]
 pub struct ApiKey(pub Result<(), AppError>);
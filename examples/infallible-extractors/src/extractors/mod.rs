@@ -1,7 +1,4 @@
 pub mod auth;
-pub mod json;
-pub mod path;
 
+pub use aide_apperror::extractors::infallible::{JsonValidate, PathValidate, QueryValidate};
 pub use auth::*;
-pub use json::*;
-pub use path::*;
@@ -7,13 +7,15 @@ use docs::docs_routes;
 use crate::{state::AppState, todos::routes::todo_routes};
 
 mod docs;
-mod error;
 mod extractors;
 mod state;
 mod todos;
 
 #[tokio::main]
 async fn main() {
+  // Logs the source of internal errors, by their `error_id`
+  tracing_subscriber::fmt::init();
+
   aide::gen::on_error(|error| {
     println!("{error}");
   });
@@ -51,7 +53,9 @@ fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
       aide::openapi::SecurityScheme::ApiKey {
         location: aide::openapi::ApiKeyLocation::Header,
         name: "X-Auth-Key".into(),
-        description: Some("A key that is ignored.".into()),
+        description: Some(
+          "`CORRECT_API_KEY`, or `CORRECT_API_KEY_BUT_NO_ACCESS` to be denied access.".into(),
+        ),
         extensions: Default::default(),
       },
     )
@@ -5,6 +5,7 @@ use aide::{
   },
   transform::TransformOperation,
 };
+use aide_apperror::{AppErrorCode, AppErrorOutput};
 use axum::{extract::State, http::StatusCode, Json};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,8 +13,7 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-  error::{AppErrorCode, AppErrorOutput},
-  extractors::{auth::ApiKey, JsonValidate, PathValidate},
+  extractors::{ApiKey, JsonValidate, PathValidate, QueryValidate},
   state::AppState,
 };
 
@@ -80,10 +80,16 @@ fn create_todo_docs(op: TransformOperation) -> TransformOperation {
     .response_with::<401, Json<AppErrorOutput>, _>(|op| op.description("Not authenticated"))
     .response_with::<403, Json<AppErrorOutput>, _>(|op| op.description("Not authorized"))
     .response_with::<400, Json<AppErrorOutput>, _>(|op| {
-      op.description("A json deserialization error occured")
+      op.description("The request body is not syntactically valid json")
+    })
+    .response_with::<413, Json<AppErrorOutput>, _>(|op| {
+      op.description("The request body exceeds the size limit")
+    })
+    .response_with::<415, Json<AppErrorOutput>, _>(|op| {
+      op.description("The request body is not of content type `application/json`")
     })
     .response_with::<422, Json<AppErrorOutput>, _>(|op| {
-      op.description("The description did not meet required length")
+      op.description("The description did not meet required length, or was not a string")
     })
 }
 
@@ -92,14 +98,47 @@ struct TodoList {
   todo_ids: Vec<Uuid>,
 }
 
-async fn list_todos(State(app): State<AppState>) -> Json<TodoList> {
-  Json(TodoList {
-    todo_ids: app.todos.lock().unwrap().keys().copied().collect(),
-  })
+/// Filters of the Todo list.
+#[derive(Deserialize, JsonSchema, Validate)]
+struct ListTodos {
+  /// Only list Todo items that are, or are not, complete.
+  complete: Option<bool>,
+  /// The maximum number of Todo items to list.
+  #[validate(range(min = 1, max = 100))]
+  limit: Option<usize>,
+}
+
+async fn list_todos(
+  State(app): State<AppState>,
+  QueryValidate(filter): QueryValidate<ListTodos>,
+) -> Result<Json<TodoList>, StatusCode> {
+  let filter = match filter {
+    Ok(v) => v,
+    Err(e) => return Err(AppErrorCode::from(e).status_code()),
+  };
+
+  let todos = app.todos.lock().unwrap();
+  let todo_ids = todos
+    .values()
+    .filter(|todo| {
+      filter
+        .complete
+        .is_none_or(|complete| todo.complete == complete)
+    })
+    .map(|todo| todo.id)
+    .take(filter.limit.unwrap_or(usize::MAX))
+    .collect();
+  Ok(Json(TodoList { todo_ids }))
 }
 
 fn list_todos_docs(op: TransformOperation) -> TransformOperation {
   op.description("List all Todo items.")
+    .response_with::<400, Json<AppErrorOutput>, _>(|op| {
+      op.description("The query string was not supplied correctly")
+    })
+    .response_with::<422, Json<AppErrorOutput>, _>(|op| {
+      op.description("The limit was not between 1 and 100")
+    })
 }
 
 #[derive(Deserialize, JsonSchema, Validate)]